@@ -0,0 +1,29 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::distributions::{Distribution, Uniform};
+use vers_vecs::{BitVec, RsVec};
+
+// compare runs with and without the `simd` feature to measure the vectorized block popcount
+fn bench_construction(b: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let sample = Uniform::new(0, u64::MAX);
+
+    let len = 1 << 30;
+    let mut bit_vec = BitVec::with_capacity(len);
+    for _ in 0..len / 64 {
+        bit_vec.append_word(sample.sample(&mut rng));
+    }
+
+    let mut group = b.benchmark_group("RsVec Construction");
+    group.sample_size(10);
+    group.bench_function("1 gigabit", |b| {
+        b.iter_batched(
+            || bit_vec.clone(),
+            |bit_vec| black_box(RsVec::from_bit_vec(bit_vec)),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_construction);
+criterion_main!(benches);
@@ -23,5 +23,42 @@ fn bench_select(b: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_select);
+fn bench_select_multi(b: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+
+    let mut group = b.benchmark_group("Select: Sorted Bulk Queries");
+    group.plot_config(common::plot_config());
+
+    let l = 10_000_000;
+    let bit_vec = common::construct_vers_vec(&mut rng, l);
+    let sample = Uniform::new(0, bit_vec.rank1(bit_vec.len()));
+    let generate_ranks = |rng: &mut rand::rngs::ThreadRng| {
+        let mut ranks = (0..10_000).map(|_| sample.sample(rng)).collect::<Vec<_>>();
+        ranks.sort_unstable();
+        ranks
+    };
+
+    group.bench_with_input(BenchmarkId::new("select1", l), &l, |b, _| {
+        b.iter_batched(
+            || generate_ranks(&mut rng),
+            |ranks| {
+                for rank in ranks {
+                    black_box(bit_vec.select1(rank));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_with_input(BenchmarkId::new("select1_multi", l), &l, |b, _| {
+        b.iter_batched(
+            || generate_ranks(&mut rng),
+            |ranks| black_box(bit_vec.select1_multi(&ranks)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_select, bench_select_multi);
 criterion_main!(benches);
@@ -0,0 +1,247 @@
+//! An append-only bit vector with rank and select support.
+//! The rank metadata is maintained while bits are appended, so queries can be answered before
+//! the vector is complete, for example during streaming construction.
+
+use crate::util::pdep::Pdep;
+use crate::{BitVec, RsVec};
+use std::mem::size_of;
+
+use super::WORD_SIZE;
+
+/// Number of bits covered by each rank sample.
+const BLOCK_SIZE: usize = 512;
+
+/// An append-only bit vector that supports `rank` and `select` queries over the bits appended
+/// so far.
+///
+/// The vector stores the number of 1-bits before every block of 512 bits, which is updated
+/// whenever a new block starts.
+/// Appending a bit thus takes amortized constant time (the amortization stems from the growth
+/// of the underlying vectors), and the metadata needs 64 bits per 512 bits of data (12.5%).
+/// Rank queries take constant time, and select queries take `O(log n)` time
+/// for a binary search over the blocks.
+///
+/// The queries are slower than those of an [`RsVec`], so if no queries are needed before the
+/// stream ends, build a [`BitVec`] instead and convert it.
+/// Once the stream ends, the vector can be converted into an [`RsVec`] with [`into_rs_vec`].
+///
+/// # Example
+/// ```rust
+/// use vers_vecs::bit_vec::dynamic::DynamicRsVec;
+///
+/// let mut vec = DynamicRsVec::new();
+/// vec.push(true);
+/// vec.push(false);
+/// vec.push(true);
+///
+/// assert_eq!(vec.rank1(2), 1);
+/// assert_eq!(vec.select1(1), 2);
+///
+/// vec.push(true);
+/// assert_eq!(vec.rank1(4), 3);
+/// ```
+///
+/// [`into_rs_vec`]: DynamicRsVec::into_rs_vec
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DynamicRsVec {
+    bits: BitVec,
+
+    /// Number of 1-bits before each block
+    block_ranks: Vec<usize>,
+
+    /// Total number of 1-bits in the vector
+    ones: usize,
+}
+
+impl DynamicRsVec {
+    /// Create a new empty vector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new empty vector with space for at least `capacity` bits.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bits: BitVec::with_capacity(capacity),
+            block_ranks: Vec::with_capacity(capacity.div_ceil(BLOCK_SIZE)),
+            ones: 0,
+        }
+    }
+
+    /// Append a bit to the end of the vector.
+    /// This takes amortized constant time.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
+    pub fn push(&mut self, bit: bool) {
+        if self.bits.len() % BLOCK_SIZE == 0 {
+            self.block_ranks.push(self.ones);
+        }
+
+        self.bits.append(bit);
+        self.ones += usize::from(bit);
+    }
+
+    /// Return the number of bits in the vector.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Return whether the vector is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Return the bit at the given position, or `None` if the position is out of bounds.
+    #[must_use]
+    pub fn get(&self, pos: usize) -> Option<u64> {
+        self.bits.get(pos)
+    }
+
+    /// Return the number of 1-bits in the vector up to but excluding the bit at the given
+    /// position. Calling this function with a position larger than the length of the vector
+    /// reports the total number of 1-bits.
+    #[must_use]
+    pub fn rank1(&self, pos: usize) -> usize {
+        if pos >= self.len() {
+            return self.ones;
+        }
+
+        let first_word = pos / BLOCK_SIZE * (BLOCK_SIZE / WORD_SIZE);
+        let last_word = pos / WORD_SIZE;
+        let full_words: usize = self.bits.data[first_word..last_word]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let partial_word =
+            (self.bits.data[last_word] & ((1 << (pos % WORD_SIZE)) - 1)).count_ones() as usize;
+
+        self.block_ranks[pos / BLOCK_SIZE] + full_words + partial_word
+    }
+
+    /// Return the number of 0-bits in the vector up to but excluding the bit at the given
+    /// position. Calling this function with a position larger than the length of the vector
+    /// reports the total number of 0-bits.
+    #[must_use]
+    pub fn rank0(&self, pos: usize) -> usize {
+        pos.min(self.len()) - self.rank1(pos)
+    }
+
+    /// Return the position of the 1-bit with the given rank (starting with rank 0).
+    /// If the rank is not smaller than the number of 1-bits, the length of the vector is
+    /// returned, mirroring [`RsVec::select1`].
+    #[must_use]
+    pub fn select1(&self, rank: usize) -> usize {
+        if rank >= self.ones {
+            return self.len();
+        }
+
+        // the first block always has rank 0, so the partition point is at least 1
+        let block = self.block_ranks.partition_point(|&r| r <= rank) - 1;
+        let mut rank = rank - self.block_ranks[block];
+        for (index, &word) in self.bits.data[block * (BLOCK_SIZE / WORD_SIZE)..]
+            .iter()
+            .enumerate()
+        {
+            let ones = word.count_ones() as usize;
+            if rank < ones {
+                return block * BLOCK_SIZE
+                    + index * WORD_SIZE
+                    + (1 << rank).pdep(word).trailing_zeros() as usize;
+            }
+            rank -= ones;
+        }
+
+        unreachable!("the number of 1-bits is inconsistent with the block ranks")
+    }
+
+    /// Convert the vector into an [`RsVec`], which answers queries faster but cannot be
+    /// extended anymore.
+    #[must_use]
+    pub fn into_rs_vec(self) -> RsVec {
+        RsVec::from_bit_vec(self.bits)
+    }
+
+    /// Returns the number of bytes used on the heap for this vector. This does not include
+    /// allocated space that is not used (e.g. by the allocation behavior of `Vec`).
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.bits.heap_size() + self.block_ranks.len() * size_of::<usize>()
+    }
+}
+
+impl Extend<bool> for DynamicRsVec {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_against_rs_vec() {
+        let mut rng = StdRng::from_seed([16; 32]);
+        let mut vec = DynamicRsVec::new();
+        let mut bits = BitVec::new();
+
+        for len in [0, 1, 63, 64, 65, 511, 512, 513, 1000, 4096, 9000] {
+            while vec.len() < len {
+                let bit = rng.gen_bool(0.3);
+                vec.push(bit);
+                bits.append(bit);
+            }
+
+            let rs = RsVec::from_bit_vec(bits.clone());
+            for pos in 0..=len + 1 {
+                assert_eq!(
+                    vec.rank1(pos),
+                    rs.rank1(pos),
+                    "rank1({pos}) at length {len}"
+                );
+                assert_eq!(
+                    vec.rank0(pos),
+                    rs.rank0(pos),
+                    "rank0({pos}) at length {len}"
+                );
+            }
+            for rank in 0..=rs.rank1(len) {
+                assert_eq!(
+                    vec.select1(rank),
+                    rs.select1(rank),
+                    "select1({rank}) at length {len}"
+                );
+            }
+        }
+
+        assert_eq!(vec.clone().into_rs_vec(), RsVec::from_bit_vec(bits));
+    }
+
+    #[test]
+    fn test_dense_and_empty() {
+        let vec = DynamicRsVec::new();
+        assert!(vec.is_empty());
+        assert_eq!(vec.rank1(0), 0);
+        assert_eq!(vec.rank0(5), 0);
+        assert_eq!(vec.select1(0), 0);
+        assert_eq!(vec.get(0), None);
+
+        let mut vec = DynamicRsVec::with_capacity(2000);
+        vec.extend(std::iter::repeat_n(true, 2000));
+        assert_eq!(vec.len(), 2000);
+        for i in 0..2000 {
+            assert_eq!(vec.rank1(i), i);
+            assert_eq!(vec.select1(i), i);
+        }
+        assert_eq!(vec.select1(2000), 2000);
+        assert_eq!(vec.get(1999), Some(1));
+    }
+}
@@ -1,7 +1,9 @@
 //! A fast succinct bit vector implementation with rank and select queries. Rank computes in
 //! constant-time, select on average in constant-time, with a logarithmic worst case.
 
+use std::cmp::Ordering;
 use std::mem::size_of;
+use std::ops::Range;
 
 #[cfg(all(
     feature = "simd",
@@ -13,6 +15,7 @@ use std::mem::size_of;
 ))]
 pub use bitset::*;
 pub use iter::*;
+pub use view::RsVecView;
 
 use crate::util::impl_vector_iterator;
 use crate::BitVec;
@@ -22,6 +25,9 @@ use super::WORD_SIZE;
 /// Size of a block in the bitvector.
 const BLOCK_SIZE: usize = 512;
 
+/// Number of bits covered by each rank sample returned by [`RsVec::rank_blocks`].
+pub const RANK_BLOCK_SIZE: usize = BLOCK_SIZE;
+
 /// Size of a super block in the bitvector. Super-blocks exist to decrease the memory overhead
 /// of block descriptors.
 /// Increasing or decreasing the super block size has negligible effect on performance of rank
@@ -94,15 +100,86 @@ pub struct RsVec {
     pub(crate) rank1: usize,
 }
 
+/// Count the zeros in each of the first `BLOCK_SIZE / WORD_SIZE` words of `words` and store them
+/// in `zeros`. If `words` is shorter than a block, the remaining counts are unspecified.
+#[cfg(not(all(
+    feature = "simd",
+    target_arch = "x86_64",
+    target_feature = "avx512vpopcntdq",
+    target_feature = "avx512vl",
+)))]
+#[inline(always)]
+fn count_block_zeros(words: &[u64], zeros: &mut [u32; BLOCK_SIZE / WORD_SIZE]) {
+    count_block_zeros_naive(words, zeros);
+}
+
+/// Count the zeros in each of the first `BLOCK_SIZE / WORD_SIZE` words of `words` and store them
+/// in `zeros`. If `words` is shorter than a block, the remaining counts are unspecified.
+///
+/// The function popcounts four words at a time using 256-bit SIMD instructions if an entire
+/// block is available, otherwise it falls back to a naive implementation.
+#[cfg(all(
+    feature = "simd",
+    target_arch = "x86_64",
+    target_feature = "avx512vpopcntdq",
+    target_feature = "avx512vl",
+))]
+#[inline(always)]
+fn count_block_zeros(words: &[u64], zeros: &mut [u32; BLOCK_SIZE / WORD_SIZE]) {
+    use std::arch::x86_64::{
+        __m256i, _mm256_loadu_si256, _mm256_popcnt_epi64, _mm256_storeu_si256,
+    };
+
+    const LANES: usize = 256 / WORD_SIZE;
+    const _: () = assert!((BLOCK_SIZE / WORD_SIZE) % LANES == 0);
+
+    if words.len() < BLOCK_SIZE / WORD_SIZE {
+        count_block_zeros_naive(words, zeros);
+        return;
+    }
+
+    let mut ones = [0u64; BLOCK_SIZE / WORD_SIZE];
+    for lane in (0..BLOCK_SIZE / WORD_SIZE).step_by(LANES) {
+        // SAFETY: the slice contains at least a full block of words, so both the load and the
+        // store stay in bounds. Unaligned loads and stores are used, so alignment doesn't matter.
+        unsafe {
+            let vector = _mm256_loadu_si256(words[lane..].as_ptr().cast::<__m256i>());
+            _mm256_storeu_si256(
+                ones[lane..].as_mut_ptr().cast::<__m256i>(),
+                _mm256_popcnt_epi64(vector),
+            );
+        }
+    }
+
+    for (zeros, ones) in zeros.iter_mut().zip(ones) {
+        // a word has at most 64 ones, so this cannot truncate
+        #[allow(clippy::cast_possible_truncation)]
+        let ones = ones as u32;
+        *zeros = WORD_SIZE as u32 - ones;
+    }
+}
+
+/// Scalar implementation of [`count_block_zeros`], which is also used for the incomplete last
+/// block of the vector.
+#[inline(always)]
+fn count_block_zeros_naive(words: &[u64], zeros: &mut [u32; BLOCK_SIZE / WORD_SIZE]) {
+    for (zeros, word) in zeros.iter_mut().zip(words) {
+        *zeros = word.count_zeros();
+    }
+}
+
 impl RsVec {
     /// Build an `RsVec` from a [`BitVec`]. This will consume the `BitVec`. Since `RsVec`s are
-    /// immutable, this is the only way to construct an `RsVec`.
+    /// immutable, all bits have to be known at construction time.
+    /// See also [`from_fn`] to construct an `RsVec` from a function over the indices.
     ///
     /// # Example
     /// See the example for `RsVec`.
     ///
     /// [`BitVec`]: BitVec
+    /// [`from_fn`]: RsVec::from_fn
     #[must_use]
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn from_bit_vec(vec: BitVec) -> RsVec {
         // Construct the block descriptor meta data. Each block descriptor contains the number of
         // zeros in the super-block, up to but excluding the block.
@@ -121,7 +198,13 @@ impl RsVec {
         let mut last_zero_select_block: usize = 0;
         let mut last_one_select_block: usize = 0;
 
+        let mut block_zeros = [0u32; BLOCK_SIZE / WORD_SIZE];
         for (idx, &word) in vec.data.iter().enumerate() {
+            // count the zeros of the entire block at once, so the popcount can be vectorized
+            if idx % (BLOCK_SIZE / WORD_SIZE) == 0 {
+                count_block_zeros(&vec.data[idx..], &mut block_zeros);
+            }
+
             // if we moved past a block boundary, append the block information for the previous
             // block and reset the counter if we moved past a super-block boundary.
             if idx % (BLOCK_SIZE / WORD_SIZE) == 0 {
@@ -141,7 +224,7 @@ impl RsVec {
             // count the zeros in the current word and add them to the counter
             // the last word may contain padding zeros, which should not be counted,
             // but since we do not append the last block descriptor, this is not a problem
-            let mut new_zeros = word.count_zeros() as usize;
+            let mut new_zeros = block_zeros[idx % (BLOCK_SIZE / WORD_SIZE)] as usize;
 
             // in the last block, remove remaining zeros of limb that aren't part of the vector
             if idx == vec.data.len() - 1 && vec.len % WORD_SIZE > 0 {
@@ -222,6 +305,33 @@ impl RsVec {
         }
     }
 
+    /// Build an `RsVec` of length `len`, where the bit at each index `i` is set if `f(i)` returns
+    /// `true`. The function is evaluated once for each index in ascending order, and the bits are
+    /// packed into words directly, so no intermediate collection of booleans is needed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::RsVec;
+    ///
+    /// let rs_vec = RsVec::from_fn(10, |i| i % 3 == 0);
+    /// assert_eq!(rs_vec.len(), 10);
+    /// assert_eq!(rs_vec.rank1(10), 4);
+    /// assert_eq!(rs_vec.select1(2), 6);
+    /// ```
+    #[must_use]
+    pub fn from_fn(len: usize, f: impl Fn(usize) -> bool) -> RsVec {
+        let mut vec = BitVec::with_capacity(len);
+        for start in (0..len).step_by(WORD_SIZE) {
+            let word_len = WORD_SIZE.min(len - start);
+            let word = (0..word_len).fold(0u64, |word, offset| {
+                word | (u64::from(f(start + offset)) << offset)
+            });
+            vec.append_bits(word, word_len);
+        }
+
+        RsVec::from_bit_vec(vec)
+    }
+
     /// Return the 0-rank of the bit at the given position. The 0-rank is the number of
     /// 0-bits in the vector up to but excluding the bit at the given position. Calling this
     /// function with an index larger than the length of the bit-vector will report the total
@@ -239,13 +349,324 @@ impl RsVec {
     /// function with an index larger than the length of the bit-vector will report the total
     /// number of 1-bits in the bit-vector.
     ///
+    /// Use [`rank1_inclusive`] to count the 1-bits up to and including the given position.
+    ///
     /// # Parameters
     /// - `pos`: The position of the bit to return the rank of.
+    ///
+    /// [`rank1_inclusive`]: RsVec::rank1_inclusive
     #[must_use]
     pub fn rank1(&self, pos: usize) -> usize {
         self.rank(false, pos)
     }
 
+    /// Return the number of 1-bits in the vector up to and including the bit at the given
+    /// position, i.e. in the range `[0, pos]`.
+    /// This is [`rank1`]`(pos + 1)`, and thus differs from [`rank1`]`(pos)` exactly by the
+    /// value of the bit at `pos`.
+    /// Calling this function with an index equal to or larger than the length of the bit-vector
+    /// will report the total number of 1-bits in the bit-vector.
+    ///
+    /// # Parameters
+    /// - `pos`: The position of the last bit to include in the count.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 1]));
+    /// assert_eq!(rs_vec.rank1(2), 1);
+    /// assert_eq!(rs_vec.rank1_inclusive(2), 2);
+    /// ```
+    ///
+    /// [`rank1`]: RsVec::rank1
+    #[must_use]
+    pub fn rank1_inclusive(&self, pos: usize) -> usize {
+        self.rank1(pos.saturating_add(1))
+    }
+
+    /// Return whether a run of equal bits starts at position `i`, i.e. whether `i` is the first
+    /// position of the vector or the bit at `i` differs from the bit at `i - 1`.
+    /// Returns `false` if `i` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 0, 1]));
+    /// assert!(rs_vec.is_run_start(0));
+    /// assert!(!rs_vec.is_run_start(1));
+    /// assert!(rs_vec.is_run_start(2));
+    /// assert!(rs_vec.is_run_start(4));
+    /// ```
+    #[must_use]
+    pub fn is_run_start(&self, i: usize) -> bool {
+        if i >= self.len {
+            return false;
+        }
+        if i == 0 {
+            return true;
+        }
+
+        // fetch both bits at once, the two lowest bits are the bits at i - 1 and i
+        let bits = self.get_bits_unchecked(i - 1, 2);
+        (bits ^ (bits >> 1)) & 1 == 1
+    }
+
+    /// Return the 1-rank of position `i` together with whether a run of equal bits starts at `i`,
+    /// i.e. `(rank1(i), is_run_start(i))` (see [`rank1`] and [`is_run_start`]).
+    /// This is useful for compression passes that need both values.
+    ///
+    /// Both values are derived from a single decode of the block containing `i`: the bit at `i`
+    /// and the bit before it are taken from the word the rank query popcounts.
+    /// Only if `i` is the first bit of a word, the bit before it is taken from the preceding word.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 0, 1]));
+    /// assert_eq!(rs_vec.rank_and_boundary(2), (2, true));
+    /// assert_eq!(rs_vec.rank_and_boundary(3), (2, false));
+    /// ```
+    ///
+    /// [`rank1`]: RsVec::rank1
+    /// [`is_run_start`]: RsVec::is_run_start
+    #[must_use]
+    pub fn rank_and_boundary(&self, i: usize) -> (usize, bool) {
+        if i >= self.len {
+            return (self.rank1, false);
+        }
+
+        let index = i / WORD_SIZE;
+        let block_index = i / BLOCK_SIZE;
+        let super_block_index = i / SUPER_BLOCK_SIZE;
+
+        let mut rank = (super_block_index * SUPER_BLOCK_SIZE)
+            - self.super_blocks[super_block_index].zeros
+            + ((block_index % (SUPER_BLOCK_SIZE / BLOCK_SIZE)) * BLOCK_SIZE)
+            - self.blocks[block_index].zeros as usize;
+        for &word in &self.data[(block_index * BLOCK_SIZE) / WORD_SIZE..index] {
+            rank += word.count_ones() as usize;
+        }
+
+        let word = self.data[index];
+        let offset = i % WORD_SIZE;
+        rank += (word & ((1 << offset) - 1)).count_ones() as usize;
+
+        let bit = word >> offset;
+        let previous = if offset > 0 {
+            word >> (offset - 1)
+        } else if index > 0 {
+            self.data[index - 1] >> (WORD_SIZE - 1)
+        } else {
+            // the first bit always starts a run
+            !bit
+        };
+
+        (rank, (bit ^ previous) & 1 == 1)
+    }
+
+    /// Return the excess of 1-bits over 0-bits in the vector up to but excluding the bit at
+    /// position `i`, i.e. `rank1(i) - rank0(i)`.
+    /// If the vector encodes a parenthesis expression with 1-bits as opening parentheses, this is
+    /// the nesting depth before position `i`, so it is 0 at the end of a balanced expression.
+    /// Calling this function with an index larger than the length of the bit-vector will report
+    /// the total excess of the bit-vector.
+    ///
+    /// Since `rank0(i) = i - rank1(i)`, the excess is `2 * rank1(i) - i`,
+    /// which only needs a single rank query.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    /// assert_eq!(rs_vec.excess_at(2), 2);
+    /// assert_eq!(rs_vec.excess_at(3), 1);
+    /// assert_eq!(rs_vec.excess_at(6), 0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)] // vectors cannot exceed 2^63 bits
+    pub fn excess_at(&self, i: usize) -> i64 {
+        let i = i.min(self.len);
+        2 * self.rank1(i) as i64 - i as i64
+    }
+
+    /// Return whether the 1-bits or the 0-bits are the strict majority in `range`, i.e.
+    /// `Some(true)` if more than half of the bits in the range are set, `Some(false)` if more than
+    /// half of the bits are not set, and `None` if there are equally many 1-bits and 0-bits.
+    /// Positions beyond the end of the vector are ignored.
+    /// An empty range has no majority, so `None` is returned.
+    ///
+    /// The number of 1-bits in the range is computed with two rank queries.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    /// assert_eq!(rs_vec.range_has_majority(0..4), Some(true));
+    /// assert_eq!(rs_vec.range_has_majority(2..6), Some(false));
+    /// assert_eq!(rs_vec.range_has_majority(0..6), None);
+    /// ```
+    #[must_use]
+    pub fn range_has_majority(&self, range: Range<usize>) -> Option<bool> {
+        let end = range.end.min(self.len);
+        let start = range.start.min(end);
+        let ones = self.rank1(end) - self.rank1(start);
+        let zeros = end - start - ones;
+
+        match ones.cmp(&zeros) {
+            Ordering::Greater => Some(true),
+            Ordering::Less => Some(false),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// Return the distance from position `i` to the next 1-bit after `i`, i.e. `j - i` where `j`
+    /// is the smallest position larger than `i` with a set bit.
+    /// If the bit at `i + 1` is set, the gap is 1.
+    /// Returns `None` if no 1-bit follows `i`.
+    ///
+    /// The gap is computed with one rank and one select query.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 0, 1, 0]));
+    /// assert_eq!(rs_vec.gap_after(0), Some(3));
+    /// assert_eq!(rs_vec.gap_after(2), Some(1));
+    /// assert_eq!(rs_vec.gap_after(3), None);
+    /// ```
+    #[must_use]
+    pub fn gap_after(&self, i: usize) -> Option<usize> {
+        let rank = self.rank1_inclusive(i);
+        if rank >= self.rank1 {
+            return None;
+        }
+
+        Some(self.select1(rank) - i)
+    }
+
+    /// Return the 1-rank of every `stride`-th position, i.e. `rank1(0), rank1(stride),
+    /// rank1(2 * stride), ...` up to the first multiple of `stride` that is not smaller than the
+    /// length of the vector, so the result has `ceil(len / stride) + 1` entries.
+    /// Positions beyond the end of the vector report the total number of 1-bits, like [`rank1`].
+    ///
+    /// The ranks are computed in a single forward pass over the vector, counting the 1-bits
+    /// between consecutive samples, and falling back to the rank metadata for strides larger
+    /// than a block. This is faster than independent rank queries if the stride is small.
+    ///
+    /// # Panics
+    /// Panics if `stride` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0, 1]));
+    /// assert_eq!(rs_vec.rank1_sampled(3), vec![0, 2, 3, 4]);
+    /// ```
+    ///
+    /// [`rank1`]: RsVec::rank1
+    #[must_use]
+    pub fn rank1_sampled(&self, stride: usize) -> Vec<usize> {
+        assert!(stride > 0, "stride must be positive");
+
+        let num_samples = self.len.div_ceil(stride) + 1;
+        let mut samples = Vec::with_capacity(num_samples);
+
+        // number of 1-bits in the words before `word`
+        let mut ones = 0;
+        let mut word = 0;
+        for pos in (0..num_samples).map(|i| i * stride) {
+            if pos >= self.len {
+                samples.push(self.rank1);
+                continue;
+            }
+
+            // skip large gaps using the rank metadata instead of counting every word
+            if pos / WORD_SIZE - word > BLOCK_SIZE / WORD_SIZE {
+                word = pos / WORD_SIZE;
+                ones = self.rank1(word * WORD_SIZE);
+            }
+            while word < pos / WORD_SIZE {
+                ones += self.data[word].count_ones() as usize;
+                word += 1;
+            }
+            let partial = self.data[word] & ((1 << (pos % WORD_SIZE)) - 1);
+            samples.push(ones + partial.count_ones() as usize);
+        }
+
+        samples
+    }
+
+    /// Summarize the vector by the number of 1-bits before the end of each of `buckets` buckets
+    /// of (almost) equal size, i.e. the `k`-th entry is `rank1(end_k)`, where `end_k` is
+    /// `(k + 1) * len / buckets` rounded down. The last entry is thus the total number of 1-bits.
+    ///
+    /// The summary allows approximate rank queries by linear interpolation between the entries
+    /// of the bucket containing the query position. Since the exact rank lies between the same
+    /// entries, the error is at most the bucket size `ceil(len / buckets)`.
+    /// The summary only takes `buckets` words of space, independent of the length of the vector,
+    /// so it can be kept around or sent elsewhere when the vector itself is too large,
+    /// trading accuracy for space by the choice of `buckets`.
+    ///
+    /// The entries are computed using one rank query each.
+    ///
+    /// # Panics
+    /// Panics if `buckets` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0, 1, 0]));
+    /// let summary = rs_vec.to_summary(4);
+    /// assert_eq!(summary, vec![2, 3, 3, 4]);
+    ///
+    /// // interpolate the rank of position 5, which lies in the third bucket 4..6
+    /// let estimate = summary[1] + (summary[2] - summary[1]) * (5 - 4) / 2;
+    /// assert!(estimate.abs_diff(rs_vec.rank1(5)) < 2);
+    /// ```
+    #[must_use]
+    pub fn to_summary(&self, buckets: usize) -> Vec<usize> {
+        assert!(buckets > 0, "number of buckets must be positive");
+
+        // split the multiplication to avoid overflows for long vectors
+        let (width, remainder) = (self.len / buckets, self.len % buckets);
+        (1..=buckets)
+            .map(|k| self.rank1(width * k + remainder * k / buckets))
+            .collect()
+    }
+
+    /// Iterate over the rank samples of the vector's blocks, which partition the vector into
+    /// chunks of [`RANK_BLOCK_SIZE`] bits (the last block may be shorter).
+    /// The `i`-th sample is the number of 1-bits before the `i`-th block, i.e.
+    /// `rank1(i * RANK_BLOCK_SIZE)`.
+    /// The samples are computed in constant time each from the existing rank metadata,
+    /// which allows building custom queries on top of the metadata without recomputing it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    /// use vers_vecs::bit_vec::fast_rs_vec::RANK_BLOCK_SIZE;
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_ones(2 * RANK_BLOCK_SIZE + 10));
+    /// let samples = rs_vec.rank_blocks().collect::<Vec<_>>();
+    /// assert_eq!(samples, vec![0, RANK_BLOCK_SIZE, 2 * RANK_BLOCK_SIZE]);
+    /// ```
+    pub fn rank_blocks(&self) -> impl ExactSizeIterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().map(|(block_index, block)| {
+            let super_block_index = block_index / (SUPER_BLOCK_SIZE / BLOCK_SIZE);
+            block_index * BLOCK_SIZE
+                - self.super_blocks[super_block_index].zeros
+                - block.zeros as usize
+        })
+    }
+
     // I measured 5-10% improvement with this. I don't know why it's not inlined by default, the
     // branch elimination profits alone should make it worth it.
     #[allow(clippy::inline_always)]
@@ -410,6 +831,118 @@ impl RsVec {
         }
     }
 
+    /// Convert the `RsVec` into its raw limbs and its length in bits.
+    /// This consumes the `RsVec`, and discards all rank and select meta-data, so the result can be
+    /// stored with minimal overhead.
+    /// The vector can be restored using [`from_raw_words`], which recomputes the meta-data.
+    ///
+    /// The last limb may contain unspecified bits beyond the length of the vector.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 1, 0]));
+    /// let (words, len) = rs_vec.into_raw_words();
+    /// assert_eq!(len, 5);
+    /// assert_eq!(words.len(), 1);
+    ///
+    /// let rs_vec = RsVec::from_raw_words(words, len);
+    /// assert_eq!(rs_vec.rank1(5), 3);
+    /// ```
+    ///
+    /// [`from_raw_words`]: RsVec::from_raw_words
+    #[must_use]
+    pub fn into_raw_words(self) -> (Vec<u64>, usize) {
+        (self.data, self.len)
+    }
+
+    /// Build an `RsVec` from raw limbs and a length in bits, as returned by [`into_raw_words`].
+    /// The rank and select meta-data is recomputed, so this takes the same time as
+    /// [`from_bit_vec`].
+    ///
+    /// Limbs that lie entirely beyond `len` bits are discarded, and bits of the last limb beyond
+    /// `len` are ignored.
+    ///
+    /// # Panics
+    /// Panics if `words` contains fewer than `len` bits.
+    ///
+    /// [`into_raw_words`]: RsVec::into_raw_words
+    /// [`from_bit_vec`]: RsVec::from_bit_vec
+    #[must_use]
+    pub fn from_raw_words(mut words: Vec<u64>, len: usize) -> RsVec {
+        assert!(
+            len <= words.len() * WORD_SIZE,
+            "the length exceeds the number of bits in the limbs"
+        );
+        words.truncate(len.div_ceil(WORD_SIZE));
+
+        RsVec::from_bit_vec(BitVec { data: words, len })
+    }
+
+    /// Compute the union of the 1-bits of this vector and `other`, i.e. their bitwise or.
+    /// The result is a new `RsVec` supporting rank and select queries.
+    ///
+    /// # Errors
+    /// Returns an error if the lengths of the vectors don't match.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let a = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 0]));
+    /// let b = RsVec::from_bit_vec(BitVec::from_bits(&[0, 0, 1, 1]));
+    ///
+    /// let union = a.union(&b).unwrap();
+    /// assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 0, 1, 1]);
+    /// assert_eq!(union.select1(2), 3);
+    /// ```
+    pub fn union(&self, other: &RsVec) -> Result<RsVec, String> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Compute the difference of the 1-bits of this vector and `other`, i.e. all bits set in
+    /// this vector but not in `other`.
+    /// The result is a new `RsVec` supporting rank and select queries.
+    ///
+    /// # Errors
+    /// Returns an error if the lengths of the vectors don't match.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let a = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 0]));
+    /// let b = RsVec::from_bit_vec(BitVec::from_bits(&[0, 0, 1, 1]));
+    ///
+    /// let difference = a.difference(&b).unwrap();
+    /// assert_eq!(difference.iter().collect::<Vec<_>>(), vec![1, 0, 0, 0]);
+    /// assert_eq!(difference.rank1(4), 1);
+    /// ```
+    pub fn difference(&self, other: &RsVec) -> Result<RsVec, String> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// Combine the limbs of this vector and `other` with `op` and build a new `RsVec` from the
+    /// result.
+    fn combine(&self, other: &RsVec, op: fn(u64, u64) -> u64) -> Result<RsVec, String> {
+        if self.len != other.len {
+            return Err(String::from("cannot combine vectors of different length"));
+        }
+
+        let data = self
+            .data
+            .iter()
+            .zip(other.data.iter())
+            .map(|(&a, &b)| op(a, b))
+            .collect();
+
+        Ok(RsVec::from_bit_vec(BitVec {
+            data,
+            len: self.len,
+        }))
+    }
+
     /// Check if two `RsVec`s are equal. For sparse vectors (either sparsely filled with 1-bits or
     /// 0-bits), this is faster than comparing the vectors bit by bit.
     /// Choose the value of `ZERO` depending on which bits are more sparse.
@@ -459,6 +992,7 @@ impl RsVec {
     ///
     /// [`sparse_equals`]: RsVec::sparse_equals
     #[must_use]
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn full_equals(&self, other: &Self) -> bool {
         if self.len() != other.len() {
             return false;
@@ -552,6 +1086,8 @@ impl From<RsVec> for BitVec {
 mod iter;
 // select code in here to keep it more organized
 mod select;
+// sub-range views in here to keep it more organized
+mod view;
 
 #[cfg(all(
     feature = "simd",
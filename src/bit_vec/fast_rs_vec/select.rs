@@ -219,6 +219,86 @@ impl super::RsVec {
         self.search_word_in_block1(rank, block_index)
     }
 
+    /// Return the positions of the 1-bits with the given ranks. See `select1`.
+    /// If the ranks are sorted in ascending order, all queries are answered in a single forward
+    /// pass over the vector, which reuses the super-block and block of the previous query instead
+    /// of searching them again.
+    /// Unsorted ranks are answered correctly, but each decreasing rank restarts the pass.
+    ///
+    /// For each rank that is larger than or equal to the number of 1-bits in the vector, `None`
+    /// is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[0, 1, 1, 0, 1, 0]));
+    /// assert_eq!(rs_vec.select1_multi(&[0, 2, 3]), vec![Some(1), Some(4), None]);
+    /// ```
+    #[must_use]
+    pub fn select1_multi(&self, ranks: &[usize]) -> Vec<Option<usize>> {
+        let mut results = Vec::with_capacity(ranks.len());
+
+        let mut last_rank = 0;
+        let mut super_block = 0;
+        let mut block_index = 0;
+
+        for &rank in ranks {
+            if rank >= self.rank1 {
+                results.push(None);
+                continue;
+            }
+
+            // restart the pass if the ranks are not sorted
+            if rank < last_rank {
+                super_block = 0;
+                block_index = 0;
+            }
+            last_rank = rank;
+
+            // only search the super block if the rank isn't in the super block of the last query
+            if self.super_blocks.len() > (super_block + 1)
+                && ((super_block + 1) * SUPER_BLOCK_SIZE - self.super_blocks[super_block + 1].zeros)
+                    <= rank
+            {
+                super_block = self.select_blocks[rank / SELECT_BLOCK_SIZE].index_1;
+
+                if self.super_blocks.len() > (super_block + 1)
+                    && ((super_block + 1) * SUPER_BLOCK_SIZE
+                        - self.super_blocks[super_block + 1].zeros)
+                        <= rank
+                {
+                    super_block = self.search_super_block1(super_block, rank);
+                }
+
+                block_index = super_block * (SUPER_BLOCK_SIZE / BLOCK_SIZE);
+            }
+
+            let mut local_rank =
+                rank - (super_block * SUPER_BLOCK_SIZE - self.super_blocks[super_block].zeros);
+
+            // only search the block if the rank isn't in the block of the last query
+            let block_at_super_block = super_block * (SUPER_BLOCK_SIZE / BLOCK_SIZE);
+            let next_block = block_index + 1;
+            if next_block < block_at_super_block + (SUPER_BLOCK_SIZE / BLOCK_SIZE)
+                && next_block < self.blocks.len()
+                && (next_block - block_at_super_block) * BLOCK_SIZE
+                    - self.blocks[next_block].zeros as usize
+                    <= local_rank
+            {
+                block_index = block_at_super_block;
+                self.search_block1(local_rank, block_at_super_block, &mut block_index);
+            }
+
+            local_rank -= (block_index - block_at_super_block) * BLOCK_SIZE
+                - self.blocks[block_index].zeros as usize;
+
+            results.push(Some(self.search_word_in_block1(local_rank, block_index)));
+        }
+
+        results
+    }
+
     /// Search for the block in a superblock that contains the rank. This function is only used
     /// internally and is not part of the public API.
     /// The function uses SIMD instructions if available, otherwise it falls back to a naive
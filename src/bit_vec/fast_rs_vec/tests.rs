@@ -1386,3 +1386,517 @@ fn test_simd_fallback() {
         SUPER_BLOCK_SIZE + 3 * BLOCK_SIZE + 1
     );
 }
+
+#[test]
+fn test_raw_words_round_trip() {
+    let mut rng = StdRng::from_seed([5; 32]);
+    let mut bv = BitVec::with_capacity(3 * SUPER_BLOCK_SIZE);
+    for _ in 0..3 * SUPER_BLOCK_SIZE + 17 {
+        bv.append_bit(rng.gen_range(0..2));
+    }
+
+    let rs = RsVec::from_bit_vec(bv);
+    let (words, len) = rs.clone().into_raw_words();
+    assert_eq!(len, rs.len());
+
+    let restored = RsVec::from_raw_words(words, len);
+    assert_eq!(restored, rs);
+    for i in 0..=rs.len() {
+        assert_eq!(restored.rank0(i), rs.rank0(i));
+        assert_eq!(restored.rank1(i), rs.rank1(i));
+    }
+    for i in 0..rs.rank0 {
+        assert_eq!(restored.select0(i), rs.select0(i));
+    }
+    for i in 0..rs.rank1 {
+        assert_eq!(restored.select1(i), rs.select1(i));
+    }
+
+    // limbs beyond the length are discarded
+    let restored = RsVec::from_raw_words(vec![u64::MAX; 4], 70);
+    assert_eq!(restored.len(), 70);
+    assert_eq!(restored.rank1(70), 70);
+    assert_eq!(restored.rank0(70), 0);
+}
+
+#[test]
+fn test_select1_multi() {
+    let mut rng = StdRng::from_seed([9; 32]);
+    let mut bv = BitVec::with_capacity(8 * SUPER_BLOCK_SIZE);
+    for _ in 0..(8 * SUPER_BLOCK_SIZE + 100) / WORD_SIZE {
+        // vary the density so that some super blocks are skipped entirely
+        let word = match rng.gen_range(0..4) {
+            0 => 0,
+            1 => rng.gen::<u64>() & rng.gen::<u64>() & rng.gen::<u64>(),
+            _ => rng.gen(),
+        };
+        bv.append_word(word);
+    }
+    let rs = RsVec::from_bit_vec(bv);
+
+    // all ranks in order, including out-of-bounds ranks
+    let ranks = (0..rs.rank1 + 10).collect::<Vec<_>>();
+    let results = rs.select1_multi(&ranks);
+    for (&rank, result) in ranks.iter().zip(results) {
+        if rank < rs.rank1 {
+            assert_eq!(result, Some(rs.select1(rank)), "rank {}", rank);
+        } else {
+            assert_eq!(result, None);
+        }
+    }
+
+    // sparse sorted ranks with duplicates
+    let mut ranks = (0..500)
+        .map(|_| rng.gen_range(0..rs.rank1))
+        .collect::<Vec<_>>();
+    ranks.sort_unstable();
+    let results = rs.select1_multi(&ranks);
+    for (&rank, result) in ranks.iter().zip(results) {
+        assert_eq!(result, Some(rs.select1(rank)), "rank {}", rank);
+    }
+
+    // unsorted ranks
+    let ranks = (0..500)
+        .map(|_| rng.gen_range(0..rs.rank1))
+        .collect::<Vec<_>>();
+    let results = rs.select1_multi(&ranks);
+    for (&rank, result) in ranks.iter().zip(results) {
+        assert_eq!(result, Some(rs.select1(rank)), "rank {}", rank);
+    }
+
+    let empty = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(empty.select1_multi(&[0, 1]), vec![None, None]);
+    assert!(empty.select1_multi(&[]).is_empty());
+}
+
+#[test]
+fn test_union_and_difference() {
+    let mut rng = StdRng::from_seed([6; 32]);
+    let mut a = BitVec::new();
+    let mut b = BitVec::new();
+    for _ in 0..300 {
+        a.append_word(rng.gen::<u64>());
+        b.append_word(rng.gen::<u64>() & rng.gen::<u64>());
+    }
+    a.append_bits(0b1011, 4);
+    b.append_bits(0b0110, 4);
+
+    let a = RsVec::from_bit_vec(a);
+    let b = RsVec::from_bit_vec(b);
+    let union = a.union(&b).unwrap();
+    let difference = a.difference(&b).unwrap();
+    assert_eq!(union.len(), a.len());
+    assert_eq!(difference.len(), a.len());
+
+    let mut union_rank = 0;
+    let mut difference_rank = 0;
+    for i in 0..a.len() {
+        assert_eq!(union.rank1(i), union_rank, "union rank at {i}");
+        assert_eq!(
+            difference.rank1(i),
+            difference_rank,
+            "difference rank at {i}"
+        );
+
+        let (x, y) = (a.get_unchecked(i), b.get_unchecked(i));
+        assert_eq!(union.get_unchecked(i), x | y);
+        assert_eq!(difference.get_unchecked(i), x & !y & 1);
+        union_rank += (x | y) as usize;
+        difference_rank += (x & !y & 1) as usize;
+    }
+    assert_eq!(union.rank1(a.len()), union_rank);
+    assert_eq!(difference.rank1(a.len()), difference_rank);
+
+    for (i, pos) in union.iter1().enumerate() {
+        assert_eq!(union.select1(i), pos);
+    }
+    for (i, pos) in difference.iter1().enumerate() {
+        assert_eq!(difference.select1(i), pos);
+    }
+}
+
+#[test]
+fn test_union_length_mismatch() {
+    let a = RsVec::from_bit_vec(BitVec::from_zeros(10));
+    let b = RsVec::from_bit_vec(BitVec::from_zeros(11));
+    assert!(a.union(&b).is_err());
+    assert!(a.difference(&b).is_err());
+    assert!(b.difference(&a).is_err());
+}
+
+#[test]
+fn test_rank1_inclusive() {
+    let mut rng = StdRng::from_seed([7; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..200 {
+        bv.append_word(rng.gen::<u64>());
+    }
+    bv.append_bits(0b101, 3);
+    let rs = RsVec::from_bit_vec(bv);
+
+    for i in 0..rs.len() {
+        assert_eq!(
+            rs.rank1_inclusive(i),
+            rs.rank1(i) + rs.get_unchecked(i) as usize,
+            "inclusive rank at {i}"
+        );
+    }
+    assert_eq!(rs.rank1_inclusive(rs.len()), rs.rank1(rs.len()));
+    assert_eq!(rs.rank1_inclusive(usize::MAX), rs.rank1(rs.len()));
+
+    let empty = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(empty.rank1_inclusive(0), 0);
+}
+
+#[test]
+fn test_view() {
+    let mut rng = StdRng::from_seed([8; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..400 {
+        bv.append_word(rng.gen::<u64>());
+    }
+    let rs = RsVec::from_bit_vec(bv);
+
+    for range in [0..rs.len(), 100..20000, 8192..16384, 777..778, 5000..5000] {
+        let view = rs.view(range.clone());
+        let start = range.start;
+        assert_eq!(view.len(), range.len());
+
+        for i in 0..=view.len() {
+            assert_eq!(
+                view.rank1(i),
+                rs.rank1(start + i) - rs.rank1(start),
+                "rank1 at {i} in {range:?}"
+            );
+            assert_eq!(view.rank0(i), rs.rank0(start + i) - rs.rank0(start));
+        }
+        assert_eq!(view.rank1(view.len() + 10), view.rank1(view.len()));
+
+        for (rank, pos) in rs.iter1().filter(|p| range.contains(p)).enumerate() {
+            assert_eq!(
+                view.select1(rank),
+                pos - start,
+                "select1 {rank} in {range:?}"
+            );
+            assert_eq!(view.get(pos - start), Some(1));
+        }
+        for (rank, pos) in rs.iter0().filter(|p| range.contains(p)).enumerate() {
+            assert_eq!(
+                view.select0(rank),
+                pos - start,
+                "select0 {rank} in {range:?}"
+            );
+            assert_eq!(view.get(pos - start), Some(0));
+        }
+        assert_eq!(view.select1(view.rank1(view.len())), view.len());
+        assert_eq!(view.select0(view.rank0(view.len())), view.len());
+        assert_eq!(view.get(view.len()), None);
+    }
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_view_out_of_bounds() {
+    let rs = RsVec::from_bit_vec(BitVec::from_zeros(10));
+    let _ = rs.view(5..11);
+}
+
+#[test]
+fn test_rank_blocks() {
+    let mut rng = StdRng::from_seed([11; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..1000 {
+        bv.append_word(rng.gen::<u64>() & rng.gen::<u64>());
+    }
+    bv.append_bits(0b110, 3);
+    let rs = RsVec::from_bit_vec(bv);
+
+    let samples = rs.rank_blocks().collect::<Vec<_>>();
+    assert_eq!(samples.len(), rs.len().div_ceil(RANK_BLOCK_SIZE));
+
+    // reconstruct rank1 from the block samples and a popcount within the block
+    let mut in_block = 0;
+    for i in 0..rs.len() {
+        if i % RANK_BLOCK_SIZE == 0 {
+            in_block = 0;
+        }
+        assert_eq!(
+            samples[i / RANK_BLOCK_SIZE] + in_block,
+            rs.rank1(i),
+            "rank at {i}"
+        );
+        in_block += rs.get_unchecked(i) as usize;
+    }
+
+    let empty = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(empty.rank_blocks().len(), 0);
+}
+
+#[test]
+fn test_block_zeros_construction() {
+    // compare the (possibly vectorized) block popcount against the scalar implementation
+    let mut rng = StdRng::from_seed([5; 32]);
+    let words = (0..BLOCK_SIZE / WORD_SIZE)
+        .map(|_| rng.gen::<u64>())
+        .chain([0, u64::MAX])
+        .collect::<Vec<_>>();
+    for start in 0..words.len() {
+        let mut expected = [0; BLOCK_SIZE / WORD_SIZE];
+        let mut actual = [0; BLOCK_SIZE / WORD_SIZE];
+        count_block_zeros_naive(&words[start..], &mut expected);
+        count_block_zeros(&words[start..], &mut actual);
+
+        let available = (words.len() - start).min(BLOCK_SIZE / WORD_SIZE);
+        assert_eq!(actual[..available], expected[..available], "start {start}");
+    }
+
+    // the rank metadata must be identical to counting the bits directly
+    let mut bit_vec = BitVec::new();
+    for _ in 0..(4 * SUPER_BLOCK_SIZE + 3 * BLOCK_SIZE) / WORD_SIZE {
+        bit_vec.append_word(rng.gen::<u64>());
+    }
+    bit_vec.append_bits(rng.gen::<u64>(), 37);
+    let rs = RsVec::from_bit_vec(bit_vec.clone());
+
+    let mut rank1 = 0;
+    for i in 0..bit_vec.len() {
+        if i % 61 == 0 {
+            assert_eq!(rs.rank1(i), rank1, "rank1 at {i}");
+            assert_eq!(rs.rank0(i), i - rank1, "rank0 at {i}");
+        }
+        rank1 += bit_vec.get_unchecked(i) as usize;
+    }
+    assert_eq!(rs.rank1(bit_vec.len()), rank1);
+    assert_eq!(rs.rank0, bit_vec.len() - rank1);
+}
+
+#[test]
+fn test_gap_after() {
+    let mut bv = BitVec::from_zeros(3 * SUPER_BLOCK_SIZE);
+    bv.flip_bit(10);
+    bv.flip_bit(11);
+    bv.flip_bit(2 * SUPER_BLOCK_SIZE + 5);
+    let rs = RsVec::from_bit_vec(bv);
+
+    // just before a set bit
+    assert_eq!(rs.gap_after(9), Some(1));
+    assert_eq!(rs.gap_after(10), Some(1));
+    assert_eq!(rs.gap_after(0), Some(10));
+
+    // in a long run of zeros spanning super blocks
+    assert_eq!(rs.gap_after(11), Some(2 * SUPER_BLOCK_SIZE - 6));
+    assert_eq!(rs.gap_after(SUPER_BLOCK_SIZE), Some(SUPER_BLOCK_SIZE + 5));
+
+    // at and past the last set bit
+    assert_eq!(rs.gap_after(2 * SUPER_BLOCK_SIZE + 5), None);
+    assert_eq!(rs.gap_after(3 * SUPER_BLOCK_SIZE - 1), None);
+    assert_eq!(rs.gap_after(usize::MAX), None);
+
+    let rs = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(rs.gap_after(0), None);
+}
+
+#[test]
+fn test_excess_at() {
+    let mut rng = StdRng::from_seed([6; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..(2 * SUPER_BLOCK_SIZE) / WORD_SIZE {
+        bv.append_word(rng.gen::<u64>());
+    }
+    bv.append_bits(rng.gen::<u64>(), 21);
+    let rs = RsVec::from_bit_vec(bv);
+
+    for i in 0..=rs.len() + 1 {
+        assert_eq!(
+            rs.excess_at(i),
+            rs.rank1(i) as i64 - rs.rank0(i) as i64,
+            "excess at {i}"
+        );
+    }
+
+    // balanced parenthesis expression
+    let rs = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 0, 0]));
+    let mut excess = 0;
+    for i in 0..rs.len() {
+        assert_eq!(rs.excess_at(i), excess);
+        excess += if rs.get_unchecked(i) == 1 { 1 } else { -1 };
+    }
+    assert_eq!(rs.excess_at(rs.len()), 0);
+
+    let rs = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(rs.excess_at(0), 0);
+}
+
+#[test]
+fn test_rank1_sampled() {
+    let mut rng = StdRng::from_seed([19; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..(SUPER_BLOCK_SIZE + 5 * BLOCK_SIZE) / WORD_SIZE {
+        bv.append_word(rng.gen::<u64>());
+    }
+    bv.append_bits(rng.gen::<u64>(), 45);
+    let rs = RsVec::from_bit_vec(bv);
+
+    for stride in [1, 2, 63, 64, 65, 100, 512, 4000, rs.len(), rs.len() + 7] {
+        let samples = rs.rank1_sampled(stride);
+        assert_eq!(
+            samples.len(),
+            rs.len().div_ceil(stride) + 1,
+            "stride {stride}"
+        );
+        for (i, &rank) in samples.iter().enumerate() {
+            assert_eq!(rank, rs.rank1(i * stride), "stride {stride}, sample {i}");
+        }
+    }
+
+    let rs = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(rs.rank1_sampled(5), vec![0]);
+}
+
+#[test]
+#[should_panic(expected = "stride must be positive")]
+fn test_rank1_sampled_zero_stride() {
+    let rs = RsVec::from_bit_vec(BitVec::from_ones(10));
+    let _ = rs.rank1_sampled(0);
+}
+
+#[test]
+fn test_rank_and_boundary() {
+    // runs of varying length, crossing word, block, and super block boundaries
+    let mut rng = StdRng::from_seed([21; 32]);
+    let mut bv = BitVec::new();
+    let mut bit = 0;
+    while bv.len() < SUPER_BLOCK_SIZE + 3 * BLOCK_SIZE {
+        for _ in 0..rng.gen_range(1..100) {
+            bv.append_bit(bit);
+        }
+        bit ^= 1;
+    }
+    let rs = RsVec::from_bit_vec(bv.clone());
+
+    for i in 0..=rs.len() + 1 {
+        let expected_boundary = i < bv.len() && (i == 0 || bv.get(i) != bv.get(i - 1));
+        assert_eq!(rs.is_run_start(i), expected_boundary, "boundary at {i}");
+        assert_eq!(
+            rs.rank_and_boundary(i),
+            (rs.rank1(i), rs.is_run_start(i)),
+            "position {i}"
+        );
+    }
+
+    let rs = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(rs.rank_and_boundary(0), (0, false));
+}
+
+#[test]
+fn test_from_fn() {
+    for len in [0, 1, 63, 64, 65, 1000, 10000] {
+        let rs = RsVec::from_fn(len, |i| i % 3 == 0);
+        assert_eq!(rs.len(), len);
+        for i in 0..len {
+            assert_eq!(rs.get(i), Some(u64::from(i % 3 == 0)), "bit {i}");
+            assert_eq!(rs.rank1(i), i.div_ceil(3), "rank1({i})");
+            assert_eq!(rs.rank0(i), i - i.div_ceil(3), "rank0({i})");
+        }
+        for rank in 0..len.div_ceil(3) {
+            assert_eq!(rs.select1(rank), rank * 3, "select1({rank})");
+        }
+        assert_eq!(rs.select1(len.div_ceil(3)), len);
+    }
+
+    let mut rng = StdRng::from_seed([20; 32]);
+    let bits = (0..5000).map(|_| rng.gen_bool(0.4)).collect::<Vec<_>>();
+    let mut bv = BitVec::new();
+    bits.iter().for_each(|&bit| bv.append(bit));
+    assert_eq!(
+        RsVec::from_fn(bits.len(), |i| bits[i]),
+        RsVec::from_bit_vec(bv)
+    );
+}
+
+#[test]
+fn test_to_summary() {
+    let mut rng = StdRng::from_seed([22; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..10_007 {
+        bv.append(rng.gen_bool(0.3));
+    }
+    let rs = RsVec::from_bit_vec(bv);
+    let len = rs.len();
+
+    for buckets in [1, 2, 7, 64, 1000, len, len + 5] {
+        let summary = rs.to_summary(buckets);
+        assert_eq!(summary.len(), buckets);
+        assert_eq!(summary.last(), Some(&rs.rank1(len)));
+
+        let end = |k: usize| (k + 1) * len / buckets;
+        let bucket_size = len.div_ceil(buckets);
+        for (k, &rank) in summary.iter().enumerate() {
+            assert_eq!(rank, rs.rank1(end(k)), "entry {k} with {buckets} buckets");
+        }
+
+        // interpolated ranks are within the bucket size of the exact rank
+        for pos in (0..=len).step_by(13) {
+            let k = (0..buckets).find(|&k| pos <= end(k)).unwrap();
+            let (start, lower) = if k == 0 {
+                (0, 0)
+            } else {
+                (end(k - 1), summary[k - 1])
+            };
+            let estimate = if end(k) == start {
+                lower
+            } else {
+                lower + (summary[k] - lower) * (pos - start) / (end(k) - start)
+            };
+            assert!(
+                estimate.abs_diff(rs.rank1(pos)) <= bucket_size,
+                "position {pos} with {buckets} buckets"
+            );
+        }
+    }
+
+    let empty = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(empty.to_summary(3), vec![0, 0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "number of buckets must be positive")]
+fn test_to_summary_zero_buckets() {
+    let _ = RsVec::from_bit_vec(BitVec::from_zeros(10)).to_summary(0);
+}
+
+#[test]
+fn test_range_has_majority() {
+    let mut rng = StdRng::from_seed([23; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..3000 {
+        bv.append(rng.gen_bool(0.5));
+    }
+    let rs = RsVec::from_bit_vec(bv.clone());
+
+    for start in (0..3000).step_by(37) {
+        for end in (start..=3000).step_by(53) {
+            let ones = (start..end).filter(|&i| bv.get(i) == Some(1)).count();
+            let zeros = end - start - ones;
+            let expected = if ones == zeros {
+                None
+            } else {
+                Some(ones > zeros)
+            };
+            assert_eq!(
+                rs.range_has_majority(start..end),
+                expected,
+                "range {start}..{end}"
+            );
+        }
+    }
+
+    let rs = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 1, 0, 0, 0, 0, 1]));
+    assert_eq!(rs.range_has_majority(0..6), None);
+    assert_eq!(rs.range_has_majority(0..3), Some(true));
+    assert_eq!(rs.range_has_majority(1..7), Some(false));
+    assert_eq!(rs.range_has_majority(3..3), None);
+    assert_eq!(rs.range_has_majority(6..100), None);
+    assert_eq!(rs.range_has_majority(7..100), Some(true));
+    assert_eq!(rs.range_has_majority(50..100), None);
+}
@@ -0,0 +1,132 @@
+// Sub-range views are in here to keep it more organized.
+
+use super::RsVec;
+use std::ops::Range;
+
+impl RsVec {
+    /// Create a view of the bits in `range` that answers rank and select queries relative to the
+    /// start of the range, without copying the bits or rebuilding the support structures.
+    /// This lets recursive algorithms operate on sub-ranges of a vector.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or if its start is greater than its end.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, RsVec};
+    ///
+    /// let rs_vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 1, 0, 1]));
+    /// let view = rs_vec.view(2..5);
+    ///
+    /// assert_eq!(view.len(), 3);
+    /// assert_eq!(view.rank1(2), 2);
+    /// assert_eq!(view.select1(1), 1);
+    /// assert_eq!(view.select0(0), 2);
+    /// ```
+    #[must_use]
+    pub fn view(&self, range: Range<usize>) -> RsVecView<'_> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "view range {}..{} out of bounds for vector of length {}",
+            range.start,
+            range.end,
+            self.len()
+        );
+
+        let ones_before = self.rank1(range.start);
+        RsVecView {
+            parent: self,
+            start: range.start,
+            len: range.len(),
+            ones_before,
+            ones: self.rank1(range.end) - ones_before,
+        }
+    }
+}
+
+/// A view of a contiguous range of an [`RsVec`] that answers rank and select queries relative to
+/// the start of the range.
+/// It is created by [`RsVec::view`], and positions and ranks of the view are offset such that the
+/// view behaves like an `RsVec` containing only the bits in the range.
+#[derive(Clone, Copy, Debug)]
+pub struct RsVecView<'a> {
+    parent: &'a RsVec,
+    start: usize,
+    len: usize,
+    ones_before: usize,
+    ones: usize,
+}
+
+impl RsVecView<'_> {
+    /// Return the number of bits in the view.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the view is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the bit at the given position relative to the start of the view.
+    /// If the position is not within the view, `None` is returned.
+    #[must_use]
+    pub fn get(&self, pos: usize) -> Option<u64> {
+        if pos >= self.len {
+            None
+        } else {
+            Some(self.parent.get_unchecked(self.start + pos))
+        }
+    }
+
+    /// Return the number of 0-bits in the view up to but excluding the bit at the given position
+    /// relative to the start of the view.
+    /// Calling this function with an index larger than the length of the view will report the
+    /// total number of 0-bits in the view.
+    #[must_use]
+    pub fn rank0(&self, pos: usize) -> usize {
+        let pos = pos.min(self.len);
+        pos - self.rank1(pos)
+    }
+
+    /// Return the number of 1-bits in the view up to but excluding the bit at the given position
+    /// relative to the start of the view.
+    /// This is `parent.rank1(start + pos) - parent.rank1(start)`.
+    /// Calling this function with an index larger than the length of the view will report the
+    /// total number of 1-bits in the view.
+    #[must_use]
+    pub fn rank1(&self, pos: usize) -> usize {
+        if pos >= self.len {
+            self.ones
+        } else {
+            self.parent.rank1(self.start + pos) - self.ones_before
+        }
+    }
+
+    /// Return the position relative to the start of the view of the 0-bit with the given rank
+    /// within the view.
+    /// If the rank is larger than the number of 0-bits in the view, the view length is returned.
+    #[must_use]
+    pub fn select0(&self, rank: usize) -> usize {
+        if rank >= self.len - self.ones {
+            self.len
+        } else {
+            let zeros_before = self.start - self.ones_before;
+            self.parent.select0(zeros_before + rank) - self.start
+        }
+    }
+
+    /// Return the position relative to the start of the view of the 1-bit with the given rank
+    /// within the view.
+    /// If the rank is larger than the number of 1-bits in the view, the view length is returned.
+    #[must_use]
+    pub fn select1(&self, rank: usize) -> usize {
+        if rank >= self.ones {
+            self.len
+        } else {
+            self.parent.select1(self.ones_before + rank) - self.start
+        }
+    }
+}
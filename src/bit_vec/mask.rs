@@ -174,6 +174,7 @@ where
     #[inline]
     #[must_use]
     #[allow(clippy::missing_panics_doc)] // can't panic because of bounds check
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn count_ones(&self) -> u64 {
         let mut ones = self
             .iter_limbs()
@@ -5,6 +5,7 @@ use crate::bit_vec::mask::MaskedBitVec;
 use crate::util::impl_vector_iterator;
 use std::cmp::min;
 use std::mem::size_of;
+use std::ops::{Index, Range};
 
 pub mod fast_rs_vec;
 
@@ -12,6 +13,10 @@ pub mod sparse;
 
 pub mod mask;
 
+pub mod representation;
+
+pub mod dynamic;
+
 /// Size of a word in bitvectors. All vectors operate on 64-bit words.
 const WORD_SIZE: usize = 64;
 
@@ -84,6 +89,7 @@ impl BitVec {
     /// Create a new bit vector with all zeros and the given length.
     /// The length is measured in bits.
     #[must_use]
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn from_zeros(len: usize) -> Self {
         let mut data = vec![0; len / WORD_SIZE];
         if len % WORD_SIZE != 0 {
@@ -95,6 +101,7 @@ impl BitVec {
     /// Create a new bit vector with all ones and the given length.
     /// The length is measured in bits.
     #[must_use]
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn from_ones(len: usize) -> Self {
         let mut data = vec![u64::MAX; len / WORD_SIZE];
         if len % WORD_SIZE != 0 {
@@ -227,6 +234,41 @@ impl BitVec {
         bv
     }
 
+    /// Construct a bit vector from a parenthesis expression given as text, e.g. for use with
+    /// [`BpTree`].
+    /// Each `'('` is appended as a one bit, and each `')'` as a zero bit. Whitespace is ignored,
+    /// so long expressions can be split into groups.
+    /// The expression is not required to be balanced.
+    ///
+    /// # Panics
+    /// Panics if the string contains any other character, reporting its byte index.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// let bv = BitVec::from_balanced_string("(()(()) ())");
+    /// assert_eq!(bv, BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 0, 1, 0, 0]));
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(bv);
+    /// assert_eq!(tree.size(), 5);
+    /// ```
+    ///
+    /// [`BpTree`]: crate::BpTree
+    #[must_use]
+    pub fn from_balanced_string(s: &str) -> Self {
+        let mut bv = Self::with_capacity(s.len());
+        for (index, c) in s.char_indices() {
+            match c {
+                '(' => bv.append(true),
+                ')' => bv.append(false),
+                c if c.is_whitespace() => {}
+                c => panic!("invalid character {c:?} at index {index} of parenthesis expression"),
+            }
+        }
+        bv
+    }
+
     /// Construct a bit vector from a slice of u64 quad words.
     /// The quad words are interpreted as limbs of the bit vector (i.e. each quad word contributes
     /// 64 bits to the bit vector).
@@ -498,6 +540,7 @@ impl BitVec {
     /// [`append_bit_u16`]: BitVec::append_bit_u16
     /// [`append_bit_u8`]: BitVec::append_bit_u8
     /// [`append_word`]: BitVec::append_word
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn append(&mut self, bit: bool) {
         if self.len % WORD_SIZE == 0 {
             self.data.push(0);
@@ -572,6 +615,7 @@ impl BitVec {
     /// [`append_bit_u16`]: BitVec::append_bit_u16
     /// [`append_bit_u8`]: BitVec::append_bit_u8
     /// [`append_word`]: BitVec::append_word
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn append_bit(&mut self, bit: u64) {
         if self.len % WORD_SIZE == 0 {
             self.data.push(0);
@@ -651,6 +695,7 @@ impl BitVec {
     /// [`append_bit_u32`]: BitVec::append_bit_u32
     /// [`append_bit_u16`]: BitVec::append_bit_u16
     /// [`append_bit_u8`]: BitVec::append_bit_u8
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn append_word(&mut self, word: u64) {
         if self.len % WORD_SIZE == 0 {
             self.data.push(word);
@@ -684,6 +729,7 @@ impl BitVec {
     ///
     /// # Panics
     /// Panics if `len` is larger than 64.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn append_bits(&mut self, bits: u64, len: usize) {
         assert!(len <= 64, "Cannot append more than 64 bits");
 
@@ -723,6 +769,7 @@ impl BitVec {
     ///
     /// [`append_bits`]: BitVec::append_bits
     /// [`drop_last`]: BitVec::drop_last
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn append_bits_unchecked(&mut self, bits: u64, len: usize) {
         if self.len % WORD_SIZE == 0 {
             self.data.push(bits);
@@ -736,6 +783,41 @@ impl BitVec {
         self.len += len;
     }
 
+    /// Append multiple bits to the bit vector, like [`append_bits`], but return an error instead
+    /// of discarding bits if `bits` has set bits beyond the `len - 1`-th bit.
+    /// This is useful when appending packed values of a fixed width, where a value that does not
+    /// fit the width would otherwise be silently truncated.
+    /// If an error is returned, the vector is unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let mut bv = BitVec::new();
+    /// assert!(bv.try_append_bits(0b1010, 4).is_ok());
+    /// assert!(bv.try_append_bits(0b1_0000, 4).is_err());
+    ///
+    /// assert_eq!(bv.len(), 4);
+    /// assert_eq!(bv.get_bits(0, 4), Some(0b1010));
+    /// ```
+    ///
+    /// # Errors
+    /// If `len` is larger than 64, or if `bits` does not fit into `len` bits, the function will
+    /// return `Err` with an error message, otherwise it will return an empty `Ok`.
+    ///
+    /// [`append_bits`]: BitVec::append_bits
+    pub fn try_append_bits(&mut self, bits: u64, len: usize) -> Result<(), &str> {
+        if len > WORD_SIZE {
+            return Err("cannot append more than 64 bits");
+        }
+        if len < WORD_SIZE && bits >> len != 0 {
+            return Err("value does not fit into the given number of bits");
+        }
+
+        self.append_bits(bits, len);
+        Ok(())
+    }
+
     /// Append the bits of another bit vector to the end of this vector.
     /// If this vector does not contain a multiple of 64 bits, the appended limbs need to be
     /// shifted to the left.
@@ -756,6 +838,76 @@ impl BitVec {
         }
     }
 
+    /// Concatenate several bit vectors into a single bit vector, such that each vector starts at
+    /// a multiple of 64 bits. This way, many small vectors can be stored or serialized as
+    /// one allocation.
+    /// Returns the concatenated vector, and the position of the first bit of each vector in it.
+    ///
+    /// The gaps between the vectors are filled with 0-bits.
+    /// Since the gaps are not recorded, the lengths of the vectors are needed to restore them
+    /// with [`unpack_at`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let a = BitVec::from_bits(&[1, 0, 1]);
+    /// let b = BitVec::from_ones(70);
+    /// let (blob, offsets) = BitVec::pack_many(&[&a, &b]);
+    ///
+    /// assert_eq!(offsets, vec![0, 64]);
+    /// assert_eq!(blob.len(), 192);
+    /// assert_eq!(blob.unpack_at(&offsets, 0, 3), Some(a));
+    /// assert_eq!(blob.unpack_at(&offsets, 1, 70), Some(b));
+    /// ```
+    ///
+    /// [`unpack_at`]: BitVec::unpack_at
+    #[must_use]
+    pub fn pack_many(vecs: &[&BitVec]) -> (BitVec, Vec<usize>) {
+        let total_len = vecs
+            .iter()
+            .map(|vec| vec.len.next_multiple_of(WORD_SIZE))
+            .sum();
+        let mut blob = BitVec::with_capacity(total_len);
+        let mut offsets = Vec::with_capacity(vecs.len());
+
+        for vec in vecs {
+            offsets.push(blob.len);
+            blob.extend_bitvec(vec);
+
+            // pad to the next word boundary, which also clears trailing garbage of the vector
+            let padding = blob.len.next_multiple_of(WORD_SIZE) - blob.len;
+            if padding > 0 {
+                blob.append_bits(0, padding);
+            }
+        }
+
+        (blob, offsets)
+    }
+
+    /// Extract the vector with the given `index` from a bit vector created by [`pack_many`],
+    /// using the `offsets` returned by it and the original length `len` of the vector.
+    ///
+    /// Returns `None` if the index is out of bounds of the offsets, or if the vector would exceed
+    /// this bit vector.
+    ///
+    /// [`pack_many`]: BitVec::pack_many
+    #[must_use]
+    pub fn unpack_at(&self, offsets: &[usize], index: usize, len: usize) -> Option<BitVec> {
+        let start = *offsets.get(index)?;
+        if start.checked_add(len)? > self.len {
+            return None;
+        }
+
+        let mut vec = BitVec::with_capacity(len);
+        for offset in (0..len).step_by(WORD_SIZE) {
+            let bits = min(WORD_SIZE, len - offset);
+            vec.append_bits(self.get_bits_unchecked(start + offset, bits), bits);
+        }
+
+        Some(vec)
+    }
+
     /// Return the length of the bit vector. The length is measured in bits.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -887,6 +1039,58 @@ impl BitVec {
             | ((value & 0x1) << (pos % WORD_SIZE));
     }
 
+    /// Overwrite `len` bits starting at the given position with the least significant bits of
+    /// `bits`, in little-endian order (i.e. the least significant bit is written to `pos`).
+    /// This is the counterpart of [`get_bits`] for updating packed values in place.
+    /// Unlike [`append_bits`], a value that does not fit into `len` bits is not truncated,
+    /// but results in an error.
+    /// If an error is returned, the vector is unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let mut bv = BitVec::pack_sequence_u64(&[3, 7, 1], 4);
+    /// bv.try_set_bits(4, 12, 4).unwrap();
+    /// assert!(bv.try_set_bits(8, 16, 4).is_err());
+    ///
+    /// assert_eq!(bv.unpack_element(1, 4), Some(12));
+    /// assert_eq!(bv.unpack_element(2, 4), Some(1));
+    /// ```
+    ///
+    /// # Errors
+    /// If `len` is larger than 64, if the bits are out of range, or if `bits` does not fit into
+    /// `len` bits, the function will return `Err` with an error message,
+    /// otherwise it will return an empty `Ok`.
+    ///
+    /// [`get_bits`]: BitVec::get_bits
+    /// [`append_bits`]: BitVec::append_bits
+    pub fn try_set_bits(&mut self, pos: usize, bits: u64, len: usize) -> Result<(), &str> {
+        if len > WORD_SIZE {
+            return Err("cannot set more than 64 bits");
+        }
+        if pos.checked_add(len).is_none_or(|end| end > self.len) {
+            return Err("out of range");
+        }
+        if len < WORD_SIZE && bits >> len != 0 {
+            return Err("value does not fit into the given number of bits");
+        }
+        if len == 0 {
+            return Ok(());
+        }
+
+        let mask = u64::MAX >> (WORD_SIZE - len);
+        let offset = pos % WORD_SIZE;
+        let limb = pos / WORD_SIZE;
+        self.data[limb] = (self.data[limb] & !(mask << offset)) | (bits << offset);
+        if offset + len > WORD_SIZE {
+            let shift = WORD_SIZE - offset;
+            self.data[limb + 1] = (self.data[limb + 1] & !(mask >> shift)) | (bits >> shift);
+        }
+
+        Ok(())
+    }
+
     /// Return whether the bit at the given position is set.
     /// If the position is larger than the length of the vector, None is returned.
     ///
@@ -926,6 +1130,25 @@ impl BitVec {
         self.get_unchecked(pos) != 0
     }
 
+    /// Return an iterator over the bits of the vector as booleans.
+    /// This is equivalent to mapping [`iter`] with `|bit| bit != 0`, and is intended for code
+    /// that treats the vector as a sequence of flags rather than of 0- and 1-bits.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bits(&[1, 0, 1]);
+    ///
+    /// assert_eq!(bv.iter_bools().collect::<Vec<_>>(), vec![true, false, true]);
+    /// ```
+    ///
+    /// [`iter`]: BitVec::iter
+    pub fn iter_bools(&self) -> impl ExactSizeIterator<Item = bool> + '_ {
+        self.iter().map(|bit| bit != 0)
+    }
+
     /// Return multiple bits at the given position.
     /// The number of bits to return is given by `len`.
     /// At most 64 bits can be returned.
@@ -1031,12 +1254,97 @@ impl BitVec {
         self.get_bits_unchecked(index * n, n)
     }
 
+    /// Clear the bits beyond the length of the vector in the last limb.
+    /// Those bits are not part of the vector, but operations like [`drop_last`] leave them
+    /// unchanged, and they may be set in limbs obtained from other sources.
+    /// Since the derived equality and hashing compare entire limbs, two vectors with the same
+    /// bits may compare unequal unless both are sanitized.
+    ///
+    /// Returns `true` if any bits were cleared.
+    ///
+    /// See also: [`is_sanitized`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bits(&[1, 0, 1, 1]);
+    /// bv.drop_last(2);
+    ///
+    /// assert!(!bv.is_sanitized());
+    /// assert!(bv.sanitize());
+    /// assert!(bv.is_sanitized());
+    /// assert_eq!(bv, BitVec::from_bits(&[1, 0]));
+    /// ```
+    ///
+    /// [`drop_last`]: BitVec::drop_last
+    /// [`is_sanitized`]: BitVec::is_sanitized
+    pub fn sanitize(&mut self) -> bool {
+        let Some(mask) = self.unused_bits_mask() else {
+            return false;
+        };
+
+        let last = self.data.last_mut().unwrap();
+        let dirty = *last & mask != 0;
+        *last &= !mask;
+        dirty
+    }
+
+    /// Check whether all bits beyond the length of the vector in the last limb are zero.
+    /// See [`sanitize`] for details.
+    ///
+    /// [`sanitize`]: BitVec::sanitize
+    #[must_use]
+    pub fn is_sanitized(&self) -> bool {
+        self.unused_bits_mask()
+            .is_none_or(|mask| self.data.last().unwrap() & mask == 0)
+    }
+
+    /// Get a mask of the bits in the last limb that are not part of the vector, or `None` if the
+    /// last limb is completely used.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
+    fn unused_bits_mask(&self) -> Option<u64> {
+        if self.len % WORD_SIZE == 0 {
+            None
+        } else {
+            Some(u64::MAX << (self.len % WORD_SIZE))
+        }
+    }
+
+    /// Compute a checksum of the vector by XOR-ing all limbs, ignoring bits beyond the length of
+    /// the vector in the last limb.
+    /// The checksum changes whenever a single bit flips, so it can detect accidental corruption,
+    /// for example after round-tripping the vector through serialization.
+    /// It is not a cryptographic hash, and it does not depend on the length of the vector,
+    /// so appending 0-bits does not change it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_bits(&[1, 0, 1, 1]);
+    /// let checksum = bv.word_xor_checksum();
+    /// assert_eq!(checksum, 0b1101);
+    ///
+    /// bv.flip_bit(1);
+    /// assert_ne!(bv.word_xor_checksum(), checksum);
+    /// ```
+    #[must_use]
+    pub fn word_xor_checksum(&self) -> u64 {
+        let mask = self.unused_bits_mask().unwrap_or(0);
+        let checksum = self.data.iter().fold(0, |checksum, &limb| checksum ^ limb);
+
+        // remove the unused bits of the last limb from the checksum again
+        checksum ^ (self.data.last().copied().unwrap_or(0) & mask)
+    }
+
     /// Return the number of ones in the bit vector. Since the bit vector doesn't store additional
     /// metadata, this value is calculated. Use [`RsVec`] for constant-time rank operations.
     ///
     /// [`RsVec`]: crate::RsVec
     #[must_use]
     #[allow(clippy::missing_panics_doc)] // can't panic because of manual bounds check
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub fn count_ones(&self) -> u64 {
         let mut ones: u64 = self.data[0..self.len / WORD_SIZE]
             .iter()
@@ -1061,6 +1369,262 @@ impl BitVec {
         self.len as u64 - self.count_ones()
     }
 
+    /// Return the Hamming distance between this vector and `other`, i.e. the number of positions
+    /// at which the bits of the two vectors differ.
+    /// The distance is computed by counting the ones in the XOR of each pair of limbs,
+    /// ignoring bits beyond the length of the vectors in the last limb.
+    ///
+    /// # Panics
+    /// Panics if the vectors have different lengths.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let a = BitVec::from_bits(&[1, 0, 1, 1, 0]);
+    /// let b = BitVec::from_bits(&[1, 1, 1, 0, 0]);
+    /// assert_eq!(a.hamming_distance(&b), 2);
+    /// assert_eq!(a.hamming_distance(&a), 0);
+    /// ```
+    #[must_use]
+    pub fn hamming_distance(&self, other: &BitVec) -> usize {
+        assert_eq!(self.len, other.len, "vectors cannot have different lengths");
+
+        let mask = self.unused_bits_mask().unwrap_or(0);
+        let last = self.data.len().saturating_sub(1);
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .enumerate()
+            .map(|(i, (&a, &b))| {
+                let diff = if i == last { (a ^ b) & !mask } else { a ^ b };
+                diff.count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Split the vector into its even-indexed and its odd-indexed bits, returning the two vectors
+    /// in this order.
+    /// This decodes two interleaved bit sequences, for example the coordinates of a
+    /// Morton code (Z-order curve).
+    /// If the length of the vector is odd, the last bit belongs to the even-indexed bits,
+    /// so the first vector is one bit longer than the second one.
+    ///
+    /// See [`interleave2`] for the inverse operation.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bits(&[1, 0, 1, 1, 0]);
+    /// let (even, odd) = bv.deinterleave2();
+    /// assert_eq!(even, BitVec::from_bits(&[1, 1, 0]));
+    /// assert_eq!(odd, BitVec::from_bits(&[0, 1]));
+    /// ```
+    ///
+    /// [`interleave2`]: BitVec::interleave2
+    #[must_use]
+    pub fn deinterleave2(&self) -> (BitVec, BitVec) {
+        let mut even = BitVec::with_capacity(self.len.div_ceil(2));
+        let mut odd = BitVec::with_capacity(self.len / 2);
+
+        for pos in (0..self.len).step_by(WORD_SIZE) {
+            let len = min(WORD_SIZE, self.len - pos);
+            let word = self.get_bits_unchecked(pos, len);
+            even.append_bits(Self::compress_even_bits(word), len.div_ceil(2));
+            odd.append_bits(Self::compress_even_bits(word >> 1), len / 2);
+        }
+
+        (even, odd)
+    }
+
+    /// Interleave the bits of two vectors into a new vector, such that the bits of `even` end up
+    /// at the even indices, and the bits of `odd` at the odd indices of the result.
+    /// This encodes two bit sequences into one, for example the coordinates of a
+    /// Morton code (Z-order curve).
+    /// The vector `even` may be one bit longer than `odd`, in which case the last bit of the
+    /// result belongs to `even`.
+    ///
+    /// This is the inverse of [`deinterleave2`].
+    ///
+    /// # Panics
+    /// Panics if the length of `even` is neither the length of `odd`, nor one more than that.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let even = BitVec::from_bits(&[1, 1, 0]);
+    /// let odd = BitVec::from_bits(&[0, 1]);
+    /// assert_eq!(BitVec::interleave2(&even, &odd), BitVec::from_bits(&[1, 0, 1, 1, 0]));
+    /// ```
+    ///
+    /// [`deinterleave2`]: BitVec::deinterleave2
+    #[must_use]
+    pub fn interleave2(even: &BitVec, odd: &BitVec) -> BitVec {
+        assert!(
+            even.len == odd.len || even.len == odd.len + 1,
+            "even vector must have the same length as the odd vector, or one bit more"
+        );
+
+        let mut result = BitVec::with_capacity(even.len + odd.len);
+        for pos in (0..even.len).step_by(WORD_SIZE / 2) {
+            let even_len = min(WORD_SIZE / 2, even.len - pos);
+            let odd_len = min(WORD_SIZE / 2, odd.len - pos);
+            let mut word = Self::spread_bits(even.get_bits_unchecked(pos, even_len));
+            if odd_len > 0 {
+                word |= Self::spread_bits(odd.get_bits_unchecked(pos, odd_len)) << 1;
+            }
+            result.append_bits(word, even_len + odd_len);
+        }
+
+        result
+    }
+
+    /// Gather the bits at even positions of `word` into its lower 32 bits.
+    fn compress_even_bits(word: u64) -> u64 {
+        let mut word = word & 0x5555_5555_5555_5555;
+        word = (word | (word >> 1)) & 0x3333_3333_3333_3333;
+        word = (word | (word >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+        word = (word | (word >> 4)) & 0x00FF_00FF_00FF_00FF;
+        word = (word | (word >> 8)) & 0x0000_FFFF_0000_FFFF;
+        (word | (word >> 16)) & 0x0000_0000_FFFF_FFFF
+    }
+
+    /// Distribute the lower 32 bits of `word` to the even positions, the inverse of
+    /// [`compress_even_bits`].
+    ///
+    /// [`compress_even_bits`]: BitVec::compress_even_bits
+    fn spread_bits(word: u64) -> u64 {
+        let mut word = word & 0x0000_0000_FFFF_FFFF;
+        word = (word | (word << 16)) & 0x0000_FFFF_0000_FFFF;
+        word = (word | (word << 8)) & 0x00FF_00FF_00FF_00FF;
+        word = (word | (word << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+        word = (word | (word << 2)) & 0x3333_3333_3333_3333;
+        (word | (word << 1)) & 0x5555_5555_5555_5555
+    }
+
+    /// Return the number of consecutive 0-bits at the beginning of the bit vector, i.e. the index
+    /// of the first 1-bit, or the length of the vector if it contains no 1-bits.
+    ///
+    /// Note that the beginning of the vector is stored in the least significant bits of the
+    /// limbs, so this does not correspond to [`u64::leading_zeros`] of the limbs.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let bv = BitVec::from_bits(&[0, 0, 1, 0, 1, 1]);
+    /// assert_eq!(bv.leading_zeros(), 2);
+    /// assert_eq!(bv.trailing_ones(), 2);
+    /// ```
+    #[must_use]
+    pub fn leading_zeros(&self) -> usize {
+        self.leading_bits(0)
+    }
+
+    /// Return the number of consecutive 1-bits at the beginning of the bit vector, i.e. the index
+    /// of the first 0-bit, or the length of the vector if it contains no 0-bits.
+    ///
+    /// See [`leading_zeros`] for the bit order.
+    ///
+    /// [`leading_zeros`]: BitVec::leading_zeros
+    #[must_use]
+    pub fn leading_ones(&self) -> usize {
+        self.leading_bits(u64::MAX)
+    }
+
+    /// Return the number of consecutive 0-bits at the end of the bit vector.
+    ///
+    /// See [`leading_zeros`] for the bit order.
+    ///
+    /// [`leading_zeros`]: BitVec::leading_zeros
+    #[must_use]
+    pub fn trailing_zeros(&self) -> usize {
+        self.trailing_bits(0)
+    }
+
+    /// Return the number of consecutive 1-bits at the end of the bit vector.
+    ///
+    /// See [`leading_zeros`] for the bit order.
+    ///
+    /// [`leading_zeros`]: BitVec::leading_zeros
+    #[must_use]
+    pub fn trailing_ones(&self) -> usize {
+        self.trailing_bits(u64::MAX)
+    }
+
+    /// Count the bits at the beginning of the vector that equal the bits in `pattern`, which must be
+    /// either all zeros or all ones.
+    fn leading_bits(&self, pattern: u64) -> usize {
+        for (i, &limb) in self.data.iter().enumerate() {
+            let diff = limb ^ pattern;
+            if diff != 0 {
+                // the first differing bit may be in the padding of the last limb
+                return min(i * WORD_SIZE + diff.trailing_zeros() as usize, self.len);
+            }
+        }
+        self.len
+    }
+
+    /// Count the bits at the end of the vector that equal the bits in `pattern`, which must be
+    /// either all zeros or all ones.
+    fn trailing_bits(&self, pattern: u64) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+
+        // shift the padding out of the last limb
+        let last = (self.len - 1) / WORD_SIZE;
+        let last_bits = self.len - last * WORD_SIZE;
+        let diff = (self.data[last] ^ pattern) << (WORD_SIZE - last_bits);
+        if diff != 0 {
+            return diff.leading_zeros() as usize;
+        }
+
+        let mut count = last_bits;
+        for &limb in self.data[..last].iter().rev() {
+            let diff = limb ^ pattern;
+            if diff != 0 {
+                return count + diff.leading_zeros() as usize;
+            }
+            count += WORD_SIZE;
+        }
+        count
+    }
+
+    /// Return the index of the first bit in which this vector and `other` differ.
+    /// Only the first `min(self.len(), other.len())` bits are compared, so `None` is returned if
+    /// the vectors are equal, or if the shorter vector is a prefix of the longer one.
+    /// Compare the lengths of the vectors to distinguish the two cases.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let a = BitVec::from_bits(&[1, 0, 1, 1]);
+    /// let b = BitVec::from_bits(&[1, 0, 0, 1]);
+    /// assert_eq!(a.first_difference(&b), Some(2));
+    /// assert_eq!(a.first_difference(&a), None);
+    ///
+    /// // the shorter vector is a prefix of the longer one
+    /// let c = BitVec::from_bits(&[1, 0]);
+    /// assert_eq!(a.first_difference(&c), None);
+    /// ```
+    #[must_use]
+    pub fn first_difference(&self, other: &BitVec) -> Option<usize> {
+        let len = min(self.len, other.len);
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .enumerate()
+            .find(|(_, (&a, &b))| a != b)
+            .map(|(i, (&a, &b))| i * WORD_SIZE + (a ^ b).trailing_zeros() as usize)
+            // the difference may be in the padding of the last limb of the shorter vector, or
+            // beyond the end of the shorter vector
+            .filter(|&pos| pos < len)
+    }
+
     /// Mask this bit vector with another bitvector using bitwise or. The mask is applied lazily
     /// whenever an operation on the resulting vector is performed.
     ///
@@ -1123,6 +1687,52 @@ impl BitVec {
         Ok(())
     }
 
+    /// Mask the bits in `range` of this bit vector with another bitvector using bitwise and,
+    /// leaving the bits outside the range untouched.
+    /// The first bit of the mask is applied to the first bit of the range.
+    /// The mask is applied immediately.
+    ///
+    /// # Errors
+    /// Returns an error if the range is out of bounds,
+    /// or if the length of the range doesn't match the mask length.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::BitVec;
+    ///
+    /// let mut bv = BitVec::from_ones(8);
+    /// bv.mask_range(2..6, &BitVec::from_bits(&[0, 1, 0, 1])).unwrap();
+    /// assert_eq!(bv, BitVec::from_bits(&[1, 1, 0, 1, 0, 1, 1, 1]));
+    /// ```
+    pub fn mask_range(&mut self, range: Range<usize>, mask: &BitVec) -> Result<(), String> {
+        if range.start > range.end || range.end > self.len {
+            return Err(String::from("range is out of bounds"));
+        }
+        if range.len() != mask.len {
+            return Err(String::from("mask cannot have different length than range"));
+        }
+
+        for offset in (0..mask.len).step_by(WORD_SIZE) {
+            let len = min(WORD_SIZE, mask.len - offset);
+            let len_mask = if len == WORD_SIZE {
+                u64::MAX
+            } else {
+                (1 << len) - 1
+            };
+            let clear = !mask.get_bits_unchecked(offset, len) & len_mask;
+
+            // the chunk may span two limbs of this vector
+            let pos = range.start + offset;
+            let shift = pos % WORD_SIZE;
+            self.data[pos / WORD_SIZE] &= !(clear << shift);
+            if shift > 0 && shift + len > WORD_SIZE {
+                self.data[pos / WORD_SIZE + 1] &= !(clear >> (WORD_SIZE - shift));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Mask this bit vector with another bitvector using bitwise xor. The mask is applied lazily
     /// whenever an operation on the resulting vector is performed.
     ///
@@ -1298,6 +1908,38 @@ impl BitVec {
 
 impl_vector_iterator! { BitVec, BitVecIter, BitVecRefIter }
 
+/// Access the bit at the given position as a `bool`, i.e. `bv[i]` is equivalent to
+/// `bv.is_bit_set(i).unwrap()`.
+/// Since the bits are packed, the returned reference points to a static `true` or `false` value.
+///
+/// # Panics
+/// Panics if the index is out of bounds.
+///
+/// # Example
+///
+/// ```rust
+/// use vers_vecs::BitVec;
+///
+/// let bv = BitVec::from_bits(&[1, 0, 1]);
+///
+/// assert!(bv[0]);
+/// assert!(!bv[1]);
+/// ```
+impl Index<usize> for BitVec {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.is_bit_set(index) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len
+            ),
+        }
+    }
+}
+
 /// Create a new bit vector from a slice of u64 values.
 /// The bits are appended in little-endian order (i.e. the least significant bit is appended first).
 /// The function will append the bits of each element to the bit vector in the order they are
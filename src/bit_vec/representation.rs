@@ -0,0 +1,79 @@
+//! A heuristic to choose the most space-efficient backing for a bit vector.
+//! The heuristic uses closed-form size estimates for each representation,
+//! so it can be evaluated before the bit vector is constructed.
+
+/// The size overhead of the [`RsVec`] rank and select structures in bits per 512 bits of data.
+///
+/// [`RsVec`]: crate::RsVec
+const DENSE_OVERHEAD_PER_BLOCK: usize = 28;
+
+/// A backing representation for a bit vector, as recommended by [`choose_representation`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Representation {
+    /// A plain bit vector with rank and select support, i.e. an [`RsVec`].
+    /// Its size depends only on the number of bits.
+    ///
+    /// [`RsVec`]: crate::RsVec
+    Dense,
+
+    /// A compressed list of the positions of the minority bits, i.e. a [`SparseRSVec`]
+    /// (constructed with [`from_bitvec_inverted`] if 0-bits are the minority).
+    ///
+    /// [`SparseRSVec`]: crate::SparseRSVec
+    /// [`from_bitvec_inverted`]: crate::SparseRSVec::from_bitvec_inverted
+    Sparse,
+}
+
+/// Recommend the most space-efficient backing for a bit vector with `num_bits` bits, `num_ones`
+/// of which are set.
+///
+/// The estimates are `n * (1 + 28/512)` bits for [`Dense`], and `k * (2 + log(n/k))` bits for
+/// [`Sparse`], where `k` is the number of minority bits.
+/// If both representations have the same estimate, `Dense` is preferred.
+///
+/// # Example
+/// ```rust
+/// use vers_vecs::bit_vec::representation::{choose_representation, Representation};
+///
+/// assert_eq!(choose_representation(1 << 20, 1 << 19), Representation::Dense);
+/// assert_eq!(choose_representation(1 << 20, 100), Representation::Sparse);
+/// ```
+///
+/// # Panics
+/// Panics if `num_ones` is larger than `num_bits`.
+///
+/// [`Dense`]: Representation::Dense
+/// [`Sparse`]: Representation::Sparse
+#[must_use]
+pub fn choose_representation(num_bits: usize, num_ones: usize) -> Representation {
+    assert!(num_ones <= num_bits, "more ones than bits");
+
+    let dense = dense_size(num_bits);
+    let sparse = elias_fano_size(num_bits, num_ones.min(num_bits - num_ones));
+
+    if dense <= sparse {
+        Representation::Dense
+    } else {
+        Representation::Sparse
+    }
+}
+
+/// Estimated size in bits of an [`RsVec`] with `num_bits` bits.
+///
+/// [`RsVec`]: crate::RsVec
+fn dense_size(num_bits: usize) -> usize {
+    num_bits + num_bits.div_ceil(512) * DENSE_OVERHEAD_PER_BLOCK
+}
+
+/// Estimated size in bits of an Elias-Fano encoding of `num_elements` values from a universe
+/// of size `universe`.
+fn elias_fano_size(universe: usize, num_elements: usize) -> usize {
+    if num_elements == 0 {
+        return 0;
+    }
+
+    let lower_bits = (universe / num_elements)
+        .checked_ilog2()
+        .map_or(0, |l| l as usize);
+    num_elements * (2 + lower_bits)
+}
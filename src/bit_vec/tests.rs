@@ -1,4 +1,7 @@
+use super::representation::{choose_representation, Representation};
 use super::BitVec;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 #[test]
 fn simple_bit_vec_test() {
@@ -484,6 +487,25 @@ fn test_from_bits() {
     assert_eq!(bv.get_bits(0, 3), Some(0b101));
 }
 
+#[test]
+fn test_from_balanced_string() {
+    let bv = BitVec::from_balanced_string("(()) ()\n(\t)");
+    assert_eq!(bv, BitVec::from_bits(&[1, 1, 0, 0, 1, 0, 1, 0]));
+
+    // unbalanced expressions are accepted
+    let bv = BitVec::from_balanced_string(")((");
+    assert_eq!(bv, BitVec::from_bits(&[0, 1, 1]));
+
+    assert!(BitVec::from_balanced_string("").is_empty());
+    assert!(BitVec::from_balanced_string("  \n ").is_empty());
+}
+
+#[test]
+#[should_panic(expected = "invalid character '1' at index 3")]
+fn test_from_balanced_string_invalid_character() {
+    let _ = BitVec::from_balanced_string("(( 1))");
+}
+
 #[test]
 fn test_from_words() {
     let bv = BitVec::from_limbs(&[1, 0, u64::MAX]);
@@ -813,3 +835,402 @@ fn test_splitting_limbs() {
     assert_eq!(left.get(0), Some(0));
     assert_eq!(right.get(0), Some(1));
 }
+
+#[test]
+fn test_leading_trailing_bits() {
+    let bv = BitVec::new();
+    assert_eq!(bv.leading_zeros(), 0);
+    assert_eq!(bv.leading_ones(), 0);
+    assert_eq!(bv.trailing_zeros(), 0);
+    assert_eq!(bv.trailing_ones(), 0);
+
+    let bv = BitVec::from_bits(&[0, 0, 0, 1, 1, 0, 1, 1, 1]);
+    assert_eq!(bv.leading_zeros(), 3);
+    assert_eq!(bv.leading_ones(), 0);
+    assert_eq!(bv.trailing_zeros(), 0);
+    assert_eq!(bv.trailing_ones(), 3);
+
+    // runs crossing limb boundaries
+    let mut bv = BitVec::from_ones(100);
+    bv.append_bits(0, 50);
+    bv.append_bit(1);
+    bv.append_bits(0, 64);
+    bv.append_bits(0, 6);
+    assert_eq!(bv.leading_ones(), 100);
+    assert_eq!(bv.leading_zeros(), 0);
+    assert_eq!(bv.trailing_zeros(), 70);
+    assert_eq!(bv.trailing_ones(), 0);
+
+    // exactly one full limb
+    let mut bv = BitVec::from_zeros(64);
+    bv.set(63, 1).unwrap();
+    assert_eq!(bv.leading_zeros(), 63);
+    assert_eq!(bv.trailing_ones(), 1);
+    assert_eq!(bv.trailing_zeros(), 0);
+}
+
+#[test]
+fn test_leading_trailing_bits_uniform() {
+    for len in [1, 63, 64, 65, 128, 200] {
+        let zeros = BitVec::from_zeros(len);
+        assert_eq!(zeros.leading_zeros(), len);
+        assert_eq!(zeros.trailing_zeros(), len);
+        assert_eq!(zeros.leading_ones(), 0);
+        assert_eq!(zeros.trailing_ones(), 0);
+
+        let ones = BitVec::from_ones(len);
+        assert_eq!(ones.leading_ones(), len);
+        assert_eq!(ones.trailing_ones(), len);
+        assert_eq!(ones.leading_zeros(), 0);
+        assert_eq!(ones.trailing_zeros(), 0);
+    }
+
+    // padding bits in the last limb must be ignored
+    let mut bv = BitVec::from_ones(70);
+    bv.drop_last(5);
+    assert_eq!(bv.leading_ones(), 65);
+    assert_eq!(bv.trailing_ones(), 65);
+    let mut bv = BitVec::from_zeros(66);
+    bv.append_bit(1);
+    bv.drop_last(1);
+    assert_eq!(bv.leading_zeros(), 66);
+    assert_eq!(bv.trailing_zeros(), 66);
+}
+
+#[test]
+fn test_first_difference() {
+    let mut a = BitVec::new();
+    for i in 0..10u64 {
+        a.append_word(i.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    }
+    a.append_bits(0b1011, 4);
+
+    let b = a.clone();
+    assert_eq!(a.first_difference(&b), None);
+    assert_eq!(a.first_difference(&a), None);
+
+    for pos in [0, 1, 63, 64, 300, a.len() - 1] {
+        let mut b = a.clone();
+        b.flip_bit(pos);
+        assert_eq!(a.first_difference(&b), Some(pos));
+        assert_eq!(b.first_difference(&a), Some(pos));
+    }
+
+    // differences beyond the shorter length are ignored
+    let mut prefix = a.clone();
+    prefix.drop_last(70);
+    assert_eq!(a.first_difference(&prefix), None);
+    assert_eq!(prefix.first_difference(&a), None);
+    let mut b = a.clone();
+    b.flip_bit(a.len() - 3);
+    assert_eq!(prefix.first_difference(&b), None);
+    b.flip_bit(5);
+    assert_eq!(prefix.first_difference(&b), Some(5));
+
+    assert_eq!(BitVec::new().first_difference(&a), None);
+}
+
+#[test]
+fn test_mask_range() {
+    let mut original = BitVec::new();
+    for i in 0..4u64 {
+        original.append_word(i.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 0xF0F0_F0F0_F0F0_F0F0);
+    }
+
+    // ranges starting and ending within limbs, spanning multiple limbs
+    for range in [37..201, 0..256, 64..128, 3..5, 100..100] {
+        let mut bv = original.clone();
+        let mut mask = BitVec::new();
+        for i in 0..range.len() {
+            mask.append_bit((i % 2) as u64);
+        }
+        bv.mask_range(range.clone(), &mask).unwrap();
+
+        for i in 0..bv.len() {
+            let expected = if range.contains(&i) {
+                original.get_unchecked(i) & mask.get_unchecked(i - range.start)
+            } else {
+                original.get_unchecked(i)
+            };
+            assert_eq!(bv.get_unchecked(i), expected, "bit {i} for range {range:?}");
+        }
+    }
+
+    let mut bv = original.clone();
+    assert!(bv.mask_range(10..20, &BitVec::from_ones(11)).is_err());
+    assert!(bv.mask_range(250..260, &BitVec::from_ones(10)).is_err());
+    assert_eq!(bv, original);
+}
+
+#[test]
+fn test_bool_accessors() {
+    let mut rng = StdRng::from_seed([9; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..4 {
+        bv.append_word(rng.gen::<u64>());
+    }
+    bv.append_bits(rng.gen::<u64>(), 13);
+
+    for i in 0..bv.len() {
+        assert_eq!(bv[i], bv.is_bit_set(i).unwrap(), "bit {i}");
+    }
+
+    let bools = bv.iter_bools().collect::<Vec<_>>();
+    assert_eq!(bools.len(), bv.len());
+    for (i, &bit) in bools.iter().enumerate() {
+        assert_eq!(bit, bv.is_bit_set_unchecked(i), "bit {i}");
+    }
+
+    assert_eq!(BitVec::new().iter_bools().count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn test_bool_index_out_of_bounds() {
+    let bv = BitVec::from_bits(&[1, 0, 1]);
+    let _ = bv[3];
+}
+
+#[test]
+fn test_sanitize() {
+    let mut bv = BitVec::from_ones(70);
+    assert!(bv.is_sanitized());
+    assert!(!bv.sanitize());
+
+    // dirty the bits beyond the length in the last limb
+    bv.data[1] |= 0xF0F0 << 6;
+    assert!(!bv.is_sanitized());
+    assert_ne!(bv, BitVec::from_ones(70));
+    assert_eq!(bv.count_ones(), 70);
+
+    assert!(bv.sanitize());
+    assert!(bv.is_sanitized());
+    assert_eq!(bv, BitVec::from_ones(70));
+    assert!(!bv.sanitize());
+
+    // dropping bits leaves them in the last limb
+    let mut bv = BitVec::from_ones(128);
+    bv.drop_last(60);
+    assert!(!bv.is_sanitized());
+    assert!(bv.sanitize());
+    assert_eq!(bv, BitVec::from_ones(68));
+
+    // full limbs and empty vectors have no bits to clear
+    let mut bv = BitVec::from_ones(128);
+    assert!(bv.is_sanitized());
+    assert!(!bv.sanitize());
+    let mut bv = BitVec::new();
+    assert!(bv.is_sanitized());
+    assert!(!bv.sanitize());
+}
+
+#[test]
+fn test_word_xor_checksum() {
+    let mut rng = StdRng::from_seed([10; 32]);
+    let mut bv = BitVec::new();
+    for _ in 0..5 {
+        bv.append_word(rng.gen::<u64>());
+    }
+    bv.append_bits(rng.gen::<u64>(), 29);
+
+    let copy = bv.clone();
+    let checksum = bv.word_xor_checksum();
+    assert_eq!(copy.word_xor_checksum(), checksum);
+
+    for i in [0, 63, 64, 200, bv.len() - 1] {
+        let mut flipped = bv.clone();
+        flipped.flip_bit(i);
+        assert_ne!(flipped.word_xor_checksum(), checksum, "flipped bit {i}");
+    }
+
+    // garbage beyond the length of the vector is ignored
+    let mut dirty = bv.clone();
+    dirty.data[5] |= u64::MAX << 29;
+    assert_eq!(dirty.word_xor_checksum(), checksum);
+
+    assert_eq!(BitVec::new().word_xor_checksum(), 0);
+}
+
+#[test]
+fn test_try_append_bits() {
+    let mut bv = BitVec::new();
+    bv.try_append_bits(0b101, 3).unwrap();
+
+    // too large values are rejected by the checked variant and truncated by the unchecked one
+    assert!(bv.try_append_bits(0b1000, 3).is_err());
+    assert_eq!(bv.len(), 3);
+    bv.append_bits(0b1110, 3);
+    assert_eq!(bv.len(), 6);
+    assert_eq!(bv.get_bits(0, 3), Some(0b101));
+    assert_eq!(bv.get_bits(3, 3), Some(0b110));
+
+    bv.try_append_bits(u64::MAX, 64).unwrap();
+    assert_eq!(bv.get_bits(6, 64), Some(u64::MAX));
+    assert!(bv.try_append_bits(0, 65).is_err());
+    bv.try_append_bits(0, 0).unwrap();
+    assert!(bv.try_append_bits(1, 0).is_err());
+    assert_eq!(bv.len(), 70);
+}
+
+#[test]
+fn test_try_set_bits() {
+    let mut rng = StdRng::from_seed([12; 32]);
+    let width = 13;
+    let mut values = (0..100)
+        .map(|_| rng.gen_range(0..1 << width))
+        .collect::<Vec<u64>>();
+    let mut bv = BitVec::pack_sequence_u64(&values, width);
+
+    for _ in 0..500 {
+        let index = rng.gen_range(0..values.len());
+        let value = rng.gen_range(0..1 << width);
+        bv.try_set_bits(index * width, value, width).unwrap();
+        values[index] = value;
+    }
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(bv.unpack_element(i, width), Some(value));
+    }
+
+    // too large values are rejected and leave the vector unchanged
+    assert!(bv.try_set_bits(0, 1 << width, width).is_err());
+    assert_eq!(bv.unpack_element(0, width), Some(values[0]));
+
+    // full words spanning two limbs
+    let mut bv = BitVec::from_zeros(200);
+    bv.try_set_bits(70, u64::MAX, 64).unwrap();
+    assert_eq!(bv.count_ones(), 64);
+    assert_eq!(bv.get_bits(70, 64), Some(u64::MAX));
+
+    assert!(bv.try_set_bits(190, 0, 11).is_err());
+    assert!(bv.try_set_bits(usize::MAX, 0, 1).is_err());
+    assert!(bv.try_set_bits(0, 0, 65).is_err());
+    bv.try_set_bits(200, 0, 0).unwrap();
+}
+
+#[test]
+fn test_interleave2() {
+    let mut rng = StdRng::from_seed([13; 32]);
+    for len in [0usize, 1, 2, 63, 64, 65, 127, 128, 129, 1000, 1001] {
+        let mut bv = BitVec::new();
+        for _ in 0..len {
+            bv.append(rng.gen_bool(0.5));
+        }
+
+        let (even, odd) = bv.deinterleave2();
+        assert_eq!(even.len(), len.div_ceil(2), "length {len}");
+        assert_eq!(odd.len(), len / 2, "length {len}");
+        for i in 0..len {
+            let expected = if i % 2 == 0 {
+                even.get(i / 2)
+            } else {
+                odd.get(i / 2)
+            };
+            assert_eq!(bv.get(i), expected, "bit {i} at length {len}");
+        }
+
+        assert_eq!(BitVec::interleave2(&even, &odd), bv, "length {len}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "even vector must have the same length as the odd vector")]
+fn test_interleave2_length_mismatch() {
+    let _ = BitVec::interleave2(&BitVec::from_zeros(3), &BitVec::from_zeros(5));
+}
+
+#[test]
+fn test_pack_many() {
+    let mut rng = StdRng::from_seed([14; 32]);
+    let vecs = [0, 1, 63, 64, 65, 200, 0, 7, 128]
+        .into_iter()
+        .map(|len| {
+            let mut bv = BitVec::new();
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            bv
+        })
+        .collect::<Vec<_>>();
+
+    let (blob, offsets) = BitVec::pack_many(&vecs.iter().collect::<Vec<_>>());
+    assert_eq!(offsets.len(), vecs.len());
+    for (i, vec) in vecs.iter().enumerate() {
+        assert_eq!(offsets[i] % 64, 0);
+        assert_eq!(blob.unpack_at(&offsets, i, vec.len()).as_ref(), Some(vec));
+    }
+    assert_eq!(
+        blob.len(),
+        vecs.iter()
+            .map(|v| v.len().next_multiple_of(64))
+            .sum::<usize>()
+    );
+    assert!(blob.is_sanitized());
+
+    // garbage bits of the input do not leak into the padding
+    let mut dirty = BitVec::from_ones(100);
+    dirty.drop_last(30);
+    let (blob, offsets) = BitVec::pack_many(&[&dirty, &BitVec::from_zeros(10)]);
+    assert_eq!(blob.count_ones(), 70);
+    assert_eq!(blob.unpack_at(&offsets, 0, 70), Some(BitVec::from_ones(70)));
+
+    assert_eq!(blob.unpack_at(&offsets, 2, 0), None);
+    assert_eq!(blob.unpack_at(&offsets, 1, 65), None);
+    assert_eq!(BitVec::pack_many(&[]), (BitVec::new(), vec![]));
+}
+
+#[test]
+fn test_hamming_distance() {
+    let mut rng = StdRng::from_seed([11; 32]);
+    for len in [0, 1, 63, 64, 65, 200, 1000] {
+        let mut a = BitVec::new();
+        let mut b = BitVec::new();
+        for _ in 0..len {
+            a.append(rng.gen_bool(0.5));
+            b.append(rng.gen_bool(0.5));
+        }
+
+        let expected = (0..len).filter(|&i| a.get(i) != b.get(i)).count();
+        assert_eq!(a.hamming_distance(&b), expected, "length {len}");
+        assert_eq!(b.hamming_distance(&a), expected, "length {len}");
+        assert_eq!(a.hamming_distance(&a), 0, "length {len}");
+    }
+
+    // garbage beyond the length of the vector is ignored
+    let mut a = BitVec::from_ones(70);
+    let b = BitVec::from_ones(70);
+    a.data[1] &= 0b111111;
+    assert_eq!(a.hamming_distance(&b), 0);
+}
+
+#[test]
+#[should_panic(expected = "vectors cannot have different lengths")]
+fn test_hamming_distance_length_mismatch() {
+    let _ = BitVec::from_zeros(10).hamming_distance(&BitVec::from_zeros(11));
+}
+
+#[test]
+fn test_choose_representation_extreme_sparse() {
+    assert_eq!(choose_representation(1 << 30, 1), Representation::Sparse);
+    assert_eq!(choose_representation(1 << 30, 1000), Representation::Sparse);
+    assert_eq!(choose_representation(1 << 20, 0), Representation::Sparse);
+
+    // almost all bits set, the zeros are sparse
+    assert_eq!(
+        choose_representation(1 << 20, (1 << 20) - 10),
+        Representation::Sparse
+    );
+}
+
+#[test]
+fn test_choose_representation_balanced_dense() {
+    assert_eq!(
+        choose_representation(1 << 20, 1 << 19),
+        Representation::Dense
+    );
+    assert_eq!(
+        choose_representation(1 << 20, 600_000),
+        Representation::Dense
+    );
+    assert_eq!(choose_representation(1000, 400), Representation::Dense);
+    assert_eq!(choose_representation(0, 0), Representation::Dense);
+}
@@ -570,6 +570,36 @@ impl EliasFanoVec {
         }
     }
 
+    /// Returns the largest difference between two consecutive elements in the vector,
+    /// or 0 if the vector has fewer than two elements.
+    /// The gap characterizes how clustered the elements are.
+    ///
+    /// The differences are computed during a single pass of the [`iter`] over the vector,
+    /// so this is faster than calling [`delta`] for each index.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::EliasFanoVec;
+    ///
+    /// let elias_fano_vec = EliasFanoVec::from_slice(&[0, 9, 29, 109, 110]);
+    /// assert_eq!(elias_fano_vec.max_gap(), 80);
+    /// ```
+    ///
+    /// [`iter`]: EliasFanoVec::iter
+    /// [`delta`]: EliasFanoVec::delta
+    #[must_use]
+    pub fn max_gap(&self) -> u64 {
+        let mut iter = self.iter();
+        let Some(first) = iter.next() else {
+            return 0;
+        };
+
+        iter.fold((first, 0), |(previous, max_gap), value| {
+            (value, max_gap.max(value - previous))
+        })
+        .1
+    }
+
     /// Return how many elements strictly smaller than the query element are present in the vector.
     #[must_use]
     #[allow(clippy::cast_possible_truncation)] // we will fix this in a breaking update
@@ -598,11 +628,86 @@ impl EliasFanoVec {
         )
     }
 
+    /// Return how many elements in the inclusive range `[lo, hi]` are present in the vector,
+    /// counting duplicates.
+    /// The count is computed from two [`rank`] queries, so no elements are decoded.
+    /// If `lo > hi`, the range is empty and 0 is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::EliasFanoVec;
+    ///
+    /// let ef = EliasFanoVec::from_slice(&[0, 4, 4, 7, 20]);
+    /// assert_eq!(ef.count_in_range(4, 7), 3);
+    /// assert_eq!(ef.count_in_range(5, 6), 0);
+    /// assert_eq!(ef.count_in_range(0, u64::MAX), 5);
+    /// ```
+    ///
+    /// [`rank`]: EliasFanoVec::rank
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // the rank is bounded by the length of the vector
+    pub fn count_in_range(&self, lo: u64, hi: u64) -> usize {
+        if lo > hi {
+            return 0;
+        }
+
+        let end = hi
+            .checked_add(1)
+            .map_or(self.len() as u64, |v| self.rank(v));
+        (end - self.rank(lo)) as usize
+    }
+
     /// Returns the number of bytes on the heap for this vector. Does not include allocated memory
     /// that isn't used.
+    /// This is the sum of [`upper_heap_size`] and [`lower_heap_size`].
+    ///
+    /// [`upper_heap_size`]: EliasFanoVec::upper_heap_size
+    /// [`lower_heap_size`]: EliasFanoVec::lower_heap_size
     #[must_use]
     pub fn heap_size(&self) -> usize {
-        self.upper_vec.heap_size() + self.lower_vec.heap_size()
+        self.upper_heap_size() + self.lower_heap_size()
+    }
+
+    /// Returns the number of bytes on the heap for the unary-coded upper bits of the elements,
+    /// including the rank and select support structures.
+    #[must_use]
+    pub fn upper_heap_size(&self) -> usize {
+        self.upper_vec.heap_size()
+    }
+
+    /// Returns the number of bytes on the heap for the fixed-width lower bits of the elements.
+    #[must_use]
+    pub fn lower_heap_size(&self) -> usize {
+        self.lower_vec.heap_size()
+    }
+
+    /// Returns the average number of heap bits used per element, including all support structures.
+    /// For `n` elements from a universe of size `u`, this is close to the theoretical
+    /// `2 + ceil(log2(u / n))` bits per element, plus the overhead of the rank and select
+    /// structures.
+    /// This allows comparing the encoding against the information-theoretic lower bound and
+    /// against a dense [`RsVec`].
+    /// An empty vector reports zero bits per element.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::EliasFanoVec;
+    ///
+    /// let data = (0..10_000).map(|i| i * 100).collect::<Vec<u64>>();
+    /// let elias_fano_vec = EliasFanoVec::from_slice(&data);
+    ///
+    /// // theory predicts around 2 + ceil(log2(100)) = 9 bits per element
+    /// let bits = elias_fano_vec.bits_per_element();
+    /// assert!(bits > 7.0 && bits < 11.0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // precision loss is acceptable for a size estimate
+    pub fn bits_per_element(&self) -> f64 {
+        if self.len == 0 {
+            0.0
+        } else {
+            (self.heap_size() * 8) as f64 / self.len as f64
+        }
     }
 }
 
@@ -611,4 +716,5 @@ impl_ef_iterator! {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::same_item_push)] // the tests predate these lints
 mod tests;
@@ -445,6 +445,62 @@ fn test_oob_rank() {
     assert_eq!(ef.rank(1), 0);
 }
 
+#[test]
+fn test_count_in_range() {
+    let values = vec![0, 1, 1, 4, 4, 4, 4, 4, 7, 99, 101, 102, 150];
+    let ef = EliasFanoVec::from_slice(&values);
+
+    for lo in 0..160 {
+        for hi in lo..160 {
+            let expected = values.iter().filter(|&&v| lo <= v && v <= hi).count();
+            assert_eq!(ef.count_in_range(lo, hi), expected, "range [{lo}, {hi}]");
+        }
+    }
+
+    // inclusive boundaries
+    assert_eq!(ef.count_in_range(4, 4), 5);
+    assert_eq!(ef.count_in_range(150, 150), 1);
+
+    // empty ranges
+    assert_eq!(ef.count_in_range(8, 98), 0);
+    assert_eq!(ef.count_in_range(10, 5), 0);
+    assert_eq!(ef.count_in_range(151, u64::MAX), 0);
+
+    // whole universe
+    assert_eq!(ef.count_in_range(0, u64::MAX), values.len());
+    assert_eq!(ef.count_in_range(u64::MAX, u64::MAX), 0);
+
+    let ef = EliasFanoVec::from_slice(&[3, u64::MAX]);
+    assert_eq!(ef.count_in_range(4, u64::MAX), 1);
+    assert_eq!(ef.count_in_range(0, u64::MAX), 2);
+
+    let ef = EliasFanoVec::from_slice(&[]);
+    assert_eq!(ef.count_in_range(0, u64::MAX), 0);
+}
+
+#[test]
+fn test_max_gap() {
+    let values = vec![3, 4, 4, 10, 17, 17, 60, 61, 100, 103];
+    let ef = EliasFanoVec::from_slice(&values);
+    let expected = values.windows(2).map(|w| w[1] - w[0]).max().unwrap();
+    assert_eq!(ef.max_gap(), expected);
+    assert_eq!(ef.max_gap(), 43);
+
+    let mut rng = StdRng::from_seed([24; 32]);
+    let mut values = (0..5000)
+        .map(|_| rng.gen_range(0..1_000_000))
+        .collect::<Vec<u64>>();
+    values.sort_unstable();
+    let ef = EliasFanoVec::from_slice(&values);
+    let expected = values.windows(2).map(|w| w[1] - w[0]).max().unwrap();
+    assert_eq!(ef.max_gap(), expected);
+
+    assert_eq!(EliasFanoVec::from_slice(&[42]).max_gap(), 0);
+    assert_eq!(EliasFanoVec::from_slice(&[7, 7, 7]).max_gap(), 0);
+    assert_eq!(EliasFanoVec::from_slice(&[]).max_gap(), 0);
+    assert_eq!(EliasFanoVec::from_slice(&[0, u64::MAX]).max_gap(), u64::MAX);
+}
+
 #[test]
 fn test_rank_binary_search() {
     const MAX_LEN: usize = 60;
@@ -524,3 +580,31 @@ fn test_empty_ef_vec() {
     assert_eq!(ef.rank(3), 0);
     assert_eq!(ef.delta(0), None);
 }
+
+#[test]
+fn test_bits_per_element() {
+    let mut rng = StdRng::from_seed([12; 32]);
+    for (n, universe) in [
+        (100_000u64, 1u64 << 30),
+        (50_000, 1 << 20),
+        (200_000, 1 << 40),
+    ] {
+        let mut data = (0..n)
+            .map(|_| rng.gen_range(0..universe))
+            .collect::<Vec<u64>>();
+        data.sort_unstable();
+        let ef = EliasFanoVec::from_slice(&data);
+
+        let theory = 2.0 + (universe as f64 / n as f64).log2().ceil();
+        let bits = ef.bits_per_element();
+        assert!(
+            (bits - theory).abs() < 1.5,
+            "{bits} bits per element, expected about {theory} for n = {n}, u = {universe}"
+        );
+
+        assert_eq!(ef.heap_size(), ef.upper_heap_size() + ef.lower_heap_size());
+        assert!(ef.lower_heap_size() > ef.upper_heap_size());
+    }
+
+    assert_eq!(EliasFanoVec::from_slice(&[]).bits_per_element(), 0.0);
+}
@@ -27,6 +27,8 @@
 //!  - [Wavelet Matrix][wavelet::WaveletMatrix] encoding `k`-bit symbols, supporting rank, select, statistical, and predecessor/successor queries in `O(k)`.
 //!  - [Succinct Tree][trees::bp::BpTree] supporting tree navigation in `O(log n)` time,
 //!    as well as subtree size, level-order, and ancestor queries, and fast depth-first iteration.
+//!  - [Sorted Integer Index][sorted_index::SortedIntIndex] combining a wavelet matrix and an
+//!    Elias-Fano vector to answer positional queries as well as queries over the sorted values.
 //!
 //! # Performance
 //! Performance was benchmarked against publicly available implementations of the same (or similar)
@@ -59,17 +61,22 @@
 //! # Crate Features
 //! - `simd` (disabled by default): Enables the use of SIMD instructions in the `RsVec`
 //!   implementation, and an additional iterator for the `RsVec` data structure.
+//!   With `avx512vpopcntdq` available, `RsVec` construction is vectorized as well.
 //! - `serde` (disabled by default): Enables serialization and deserialization support for all
 //!   data structures in this crate using the `serde` crate.
 //! - `bp_u16_lookup` (disabled by default): Uses a 16-bit lookup table for the balanced parenthesis
 //!   tree data structure. This is faster, but requires 128 KiB instead of 4 KiB.
+//! - `rayon` (disabled by default): Enables `BpTree::from_bit_vector_parallel`, which builds the
+//!   min-max tree of very large balanced parenthesis trees in parallel using the `rayon` crate.
 
+pub use bit_vec::dynamic::DynamicRsVec;
 pub use bit_vec::fast_rs_vec::RsVec;
 pub use bit_vec::sparse::SparseRSVec;
 pub use bit_vec::BitVec;
 pub use elias_fano::EliasFanoVec;
 pub use rmq::binary_rmq::BinaryRmq;
 pub use rmq::fast_rmq::FastRmq;
+pub use sorted_index::SortedIntIndex;
 pub use trees::bp::{BpBuilder, BpTree};
 pub use trees::{IsAncestor, LevelTree, SubtreeSize, Tree, TreeBuilder};
 pub use wavelet::WaveletMatrix;
@@ -82,6 +89,9 @@ pub mod elias_fano;
 #[forbid(unsafe_code)]
 pub mod rmq;
 
+#[forbid(unsafe_code)]
+pub mod sorted_index;
+
 #[forbid(unsafe_code)]
 pub mod trees;
 
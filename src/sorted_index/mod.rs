@@ -0,0 +1,165 @@
+//! This module contains an index over an integer sequence that supports queries both in the
+//! original order of the sequence and in sorted order.
+
+use crate::{EliasFanoVec, WaveletMatrix};
+
+/// An index over a (possibly unsorted) sequence of integers, which answers positional queries
+/// over the original order of the sequence as well as rank and select queries over the sorted
+/// values.
+///
+/// The original sequence is stored in a [`WaveletMatrix`], and a sorted copy of the sequence is
+/// stored in an [`EliasFanoVec`].
+/// Keeping both representations means the index needs the memory of both data structures:
+/// roughly `n * k` bits plus rank/select overhead for the wavelet matrix, where `k` is the
+/// number of bits of the largest element, and roughly `n * (2 + log(u/n))` bits for the
+/// Elias-Fano vector, where `u` is the largest element.
+/// If only one kind of query is needed, use the respective data structure directly.
+///
+/// # Example
+/// ```rust
+/// use vers_vecs::sorted_index::SortedIntIndex;
+///
+/// let index = SortedIntIndex::from_slice(&[42, 7, 13, 7, 100]);
+///
+/// // positional access in the original order
+/// assert_eq!(index.access(0), Some(42));
+/// assert_eq!(index.access(4), Some(100));
+///
+/// // queries over the sorted values
+/// assert_eq!(index.nth_smallest(0), Some(7));
+/// assert_eq!(index.nth_smallest(2), Some(13));
+/// assert_eq!(index.rank(13), 2);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SortedIntIndex {
+    sequence: WaveletMatrix,
+    sorted: EliasFanoVec,
+}
+
+impl SortedIntIndex {
+    /// Create a new index over the given sequence of integers.
+    /// The sequence does not need to be sorted.
+    /// The values are encoded with as many bits as the largest value needs.
+    #[must_use]
+    pub fn from_slice(data: &[u64]) -> Self {
+        // use at least one bit, so sequences of zeros are not considered empty
+        let max = data.iter().copied().max().unwrap_or(0);
+        #[allow(clippy::cast_possible_truncation)] // at most 64
+        let bits_per_element = (u64::BITS - max.leading_zeros()).max(1) as u16;
+
+        let mut sorted = data.to_vec();
+        sorted.sort_unstable();
+
+        Self {
+            sequence: WaveletMatrix::from_slice(data, bits_per_element),
+            sorted: EliasFanoVec::from_slice(&sorted),
+        }
+    }
+
+    /// Get the value at position `i` of the original sequence, or `None` if `i` is out of bounds.
+    #[must_use]
+    pub fn access(&self, i: usize) -> Option<u64> {
+        self.sequence.get_u64(i)
+    }
+
+    /// Get the `k`-th smallest value of the sequence (starting with `k = 0`),
+    /// or `None` if `k` is not smaller than the length of the sequence.
+    #[must_use]
+    pub fn nth_smallest(&self, k: usize) -> Option<u64> {
+        self.sorted.get(k)
+    }
+
+    /// Return how many values in the sequence are strictly smaller than `value`.
+    #[must_use]
+    pub fn rank(&self, value: u64) -> usize {
+        // the rank is bounded by the length of the vector, so it fits into usize
+        #[allow(clippy::cast_possible_truncation)]
+        let rank = self.sorted.rank(value) as usize;
+        rank
+    }
+
+    /// Return the number of values in the sequence.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    /// Return whether the sequence is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    /// Get a reference to the wavelet matrix storing the sequence in its original order.
+    #[must_use]
+    pub fn sequence(&self) -> &WaveletMatrix {
+        &self.sequence
+    }
+
+    /// Get a reference to the Elias-Fano vector storing the sorted values.
+    #[must_use]
+    pub fn sorted(&self) -> &EliasFanoVec {
+        &self.sorted
+    }
+
+    /// Returns the number of bytes used on the heap by both underlying data structures.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.sequence.heap_size() + self.sorted.heap_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_random_sequence() {
+        let mut rng = StdRng::from_seed([12; 32]);
+        let data = (0..2000)
+            .map(|_| rng.gen_range(0..10_000u64))
+            .collect::<Vec<_>>();
+        let index = SortedIntIndex::from_slice(&data);
+
+        let mut sorted = data.clone();
+        sorted.sort_unstable();
+
+        assert_eq!(index.len(), data.len());
+        for (i, &value) in data.iter().enumerate() {
+            assert_eq!(index.access(i), Some(value), "access at {i}");
+        }
+        for (k, &value) in sorted.iter().enumerate() {
+            assert_eq!(index.nth_smallest(k), Some(value), "{k}-th smallest");
+        }
+        for value in [0, 1, 500, 5000, 9999, 10_000, u64::MAX] {
+            assert_eq!(index.rank(value), sorted.partition_point(|&v| v < value));
+        }
+
+        assert_eq!(index.access(data.len()), None);
+        assert_eq!(index.nth_smallest(data.len()), None);
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let index = SortedIntIndex::from_slice(&[]);
+        assert!(index.is_empty());
+        assert_eq!(index.access(0), None);
+        assert_eq!(index.nth_smallest(0), None);
+        assert_eq!(index.rank(5), 0);
+
+        let index = SortedIntIndex::from_slice(&[0, 0, 0]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.access(2), Some(0));
+        assert_eq!(index.nth_smallest(2), Some(0));
+        assert_eq!(index.rank(0), 0);
+        assert_eq!(index.rank(1), 3);
+
+        let index = SortedIntIndex::from_slice(&[u64::MAX, 0]);
+        assert_eq!(index.access(0), Some(u64::MAX));
+        assert_eq!(index.nth_smallest(0), Some(0));
+        assert_eq!(index.nth_smallest(1), Some(u64::MAX));
+    }
+}
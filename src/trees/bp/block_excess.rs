@@ -0,0 +1,237 @@
+//! This module provides a prefix-excess index over a single block of a parenthesis expression.
+//! After linear preprocessing, the index finds the first position in the block with a given
+//! excess using a binary search instead of scanning the block, which pays off if multiple
+//! searches resolve in the same block.
+
+use crate::RsVec;
+
+/// Cumulative excess of a single block of a parenthesis expression, grouped by excess value.
+#[derive(Clone, Debug)]
+pub(crate) struct BlockExcess {
+    /// Position of the first bit of the block in the parenthesis expression
+    start: usize,
+
+    /// Excess after each bit in the block, relative to the start of the block
+    prefix: Box<[i64]>,
+
+    /// Offsets into the block, sorted by their prefix excess, ties sorted by offset
+    offsets: Box<[usize]>,
+
+    /// The offsets with prefix excess `e` are `offsets[bounds[e + len]..bounds[e + len + 1]]`,
+    /// where `len` is the number of bits in the block
+    bounds: Box<[usize]>,
+}
+
+impl BlockExcess {
+    /// Build the index for the `len` bits of `vec` starting at `start`.
+    ///
+    /// # Panics
+    /// May panic if the block exceeds the bounds of `vec`.
+    pub(crate) fn new(vec: &RsVec, start: usize, len: usize) -> Self {
+        let mut prefix = Vec::with_capacity(len);
+        let mut excess = 0i64;
+        for i in start..start + len {
+            excess += if vec.get_unchecked(i) == super::OPEN_PAREN {
+                1
+            } else {
+                -1
+            };
+            prefix.push(excess);
+        }
+
+        // counting sort of the offsets by their prefix excess
+        let mut bounds = vec![0; 2 * len + 2];
+        for &excess in &prefix {
+            bounds[Self::bucket(excess, len) + 1] += 1;
+        }
+        for i in 1..bounds.len() {
+            bounds[i] += bounds[i - 1];
+        }
+
+        let mut offsets = vec![0; len];
+        let mut next = bounds.clone();
+        for (offset, &excess) in prefix.iter().enumerate() {
+            let slot = &mut next[Self::bucket(excess, len)];
+            offsets[*slot] = offset;
+            *slot += 1;
+        }
+
+        Self {
+            start,
+            prefix: prefix.into_boxed_slice(),
+            offsets: offsets.into_boxed_slice(),
+            bounds: bounds.into_boxed_slice(),
+        }
+    }
+
+    /// Index of the bucket for the given excess value in `bounds`
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // |excess| <= len
+    fn bucket(excess: i64, len: usize) -> usize {
+        (excess + len as i64) as usize
+    }
+
+    /// Position of the first bit of the block
+    pub(crate) fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Search for the first position in the block at or after offset `from` where the excess
+    /// relative to the position just before `from` is `relative_excess`.
+    /// This has the same semantics as a linear scan of the block starting at offset `from`.
+    ///
+    /// Returns `Ok(position)` with the absolute position if it exists in the block,
+    /// or `Err(relative_excess)` with the desired excess relative to the end of the block,
+    /// which can be used to continue the search in the min-max tree.
+    pub(crate) fn fwd_search(&self, from: usize, relative_excess: i64) -> Result<usize, i64> {
+        let len = self.prefix.len();
+        let base = if from == 0 { 0 } else { self.prefix[from - 1] };
+        let target = base + relative_excess;
+
+        if target.unsigned_abs() <= len as u64 {
+            let bucket = Self::bucket(target, len);
+            let candidates = &self.offsets[self.bounds[bucket]..self.bounds[bucket + 1]];
+            let first = candidates.partition_point(|&offset| offset < from);
+            if let Some(&offset) = candidates.get(first) {
+                return Ok(self.start + offset);
+            }
+        }
+
+        Err(target - self.prefix.last().copied().unwrap_or(0))
+    }
+}
+
+/// Cache of the prefix-excess indices of the two most recently used blocks.
+/// Two slots suffice for searches that leave their block: the block the search starts in and
+/// the block it resolves in both stay cached, so consecutive queries alternating between them
+/// don't rebuild either index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BlockExcessCache {
+    /// The most recently used index first
+    blocks: [Option<BlockExcess>; 2],
+
+    /// Number of indices built by the cache, so tests can observe the reuse
+    #[cfg(test)]
+    builds: usize,
+}
+
+impl BlockExcessCache {
+    /// Get the index for the `len` bits of `vec` starting at `start`, building it only if neither
+    /// cached index belongs to the block. A new index replaces the least recently used one.
+    ///
+    /// # Panics
+    /// May panic if the block exceeds the bounds of `vec`.
+    pub(crate) fn get(&mut self, vec: &RsVec, start: usize, len: usize) -> &BlockExcess {
+        let cached =
+            |slot: &Option<BlockExcess>| slot.as_ref().is_some_and(|block| block.start() == start);
+
+        if !cached(&self.blocks[0]) {
+            if cached(&self.blocks[1]) {
+                self.blocks.swap(0, 1);
+            } else {
+                self.blocks[1] = self.blocks[0].replace(BlockExcess::new(vec, start, len));
+
+                #[cfg(test)]
+                {
+                    self.builds += 1;
+                }
+            }
+        }
+        self.blocks[0].as_ref().unwrap()
+    }
+
+    /// Number of indices built so far, i.e. the number of lookups that could not reuse the
+    /// cached index
+    #[cfg(test)]
+    pub(crate) fn builds(&self) -> usize {
+        self.builds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitVec;
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    /// Reference implementation of the block search using a linear scan
+    fn linear_fwd_search(
+        vec: &RsVec,
+        start: usize,
+        len: usize,
+        from: usize,
+        mut relative_excess: i64,
+    ) -> Result<usize, i64> {
+        for i in start + from..start + len {
+            relative_excess -= if vec.get_unchecked(i) == 1 { 1 } else { -1 };
+            if relative_excess == 0 {
+                return Ok(i);
+            }
+        }
+        Err(relative_excess)
+    }
+
+    #[test]
+    fn test_block_excess_simple() {
+        let vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0, 1, 0]));
+        let block = BlockExcess::new(&vec, 0, 8);
+
+        assert_eq!(block.start(), 0);
+        assert_eq!(block.fwd_search(1, -1), Ok(5));
+        assert_eq!(block.fwd_search(2, -1), Ok(2));
+        assert_eq!(block.fwd_search(0, 2), Ok(1));
+        assert_eq!(block.fwd_search(0, -1), Err(-1));
+        assert_eq!(block.fwd_search(8, -1), Err(-1));
+        assert_eq!(block.fwd_search(3, 20), Err(21));
+    }
+
+    #[test]
+    fn test_block_excess_cache_reuse() {
+        let vec = RsVec::from_bit_vec(BitVec::from_bits(&[1, 1, 0, 1, 0, 0, 1, 0]));
+        let mut cache = BlockExcessCache::default();
+
+        assert_eq!(cache.get(&vec, 0, 4).fwd_search(2, -1), Ok(2));
+        assert_eq!(cache.get(&vec, 0, 4).fwd_search(0, 2), Ok(1));
+        assert_eq!(cache.builds(), 1);
+
+        assert_eq!(cache.get(&vec, 4, 4).fwd_search(0, -1), Ok(4));
+        assert_eq!(cache.get(&vec, 4, 4).fwd_search(3, -1), Ok(7));
+        assert_eq!(cache.builds(), 2);
+
+        // switching back to the previous block reuses its index
+        assert_eq!(cache.get(&vec, 0, 4).fwd_search(2, -1), Ok(2));
+        assert_eq!(cache.get(&vec, 4, 4).fwd_search(0, -1), Ok(4));
+        assert_eq!(cache.builds(), 2);
+
+        // a third block evicts the least recently used one
+        assert_eq!(cache.get(&vec, 2, 4).fwd_search(0, -1), Ok(2));
+        assert_eq!(cache.get(&vec, 4, 4).fwd_search(0, -1), Ok(4));
+        assert_eq!(cache.builds(), 3);
+        assert_eq!(cache.get(&vec, 0, 4).fwd_search(2, -1), Ok(2));
+        assert_eq!(cache.builds(), 4);
+    }
+
+    #[test]
+    fn test_block_excess_against_linear_scan() {
+        let mut rng = StdRng::from_seed([4; 32]);
+        let mut bit_vec = BitVec::new();
+        for _ in 0..8 {
+            bit_vec.append_word(rng.next_u64());
+        }
+        let vec = RsVec::from_bit_vec(bit_vec);
+
+        for (start, len) in [(0, 512), (64, 100), (300, 1), (511, 1), (0, 0)] {
+            // build once and reuse the index for all queries in the block
+            let block = BlockExcess::new(&vec, start, len);
+            for from in 0..=len {
+                for relative_excess in -12..=12 {
+                    assert_eq!(
+                        block.fwd_search(from, relative_excess),
+                        linear_fwd_search(&vec, start, len, from, relative_excess),
+                        "block {start}+{len}, from {from}, relative excess {relative_excess}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -4,11 +4,13 @@
 //! The tree is succinct (ideally sublinear space overhead) and pointer-less.
 
 use crate::bit_vec::fast_rs_vec::SelectIntoIter;
-use crate::trees::mmt::MinMaxTree;
+use crate::trees::mmt::{AdaptiveMinMaxTree, MinMaxTree64};
 use crate::trees::{IsAncestor, LevelTree, SubtreeSize, Tree};
 use crate::{BitVec, RsVec};
 use std::cmp::{max, min};
+use std::io::{self, Read, Write};
 use std::iter::FusedIterator;
+use std::ops::Range;
 
 /// The default block size for the tree, used in several const generics
 const DEFAULT_BLOCK_SIZE: usize = 512;
@@ -20,6 +22,11 @@ mod builder;
 // re-export the builders toplevel
 pub use builder::BpBuilder;
 
+pub use crate::trees::mmt::ExcessError;
+
+mod block_excess;
+use block_excess::{BlockExcess, BlockExcessCache};
+
 #[cfg(feature = "bp_u16_lookup")]
 mod lookup;
 #[cfg(feature = "bp_u16_lookup")]
@@ -138,21 +145,338 @@ use lookup_query::{process_block_bwd, process_block_fwd, LOOKUP_BLOCK_SIZE};
 /// [`BpBuilder`]: BpBuilder
 /// [`BitVec`]: BitVec
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BpTree<const BLOCK_SIZE: usize = DEFAULT_BLOCK_SIZE> {
     vec: RsVec,
-    min_max_tree: MinMaxTree,
+    min_max_tree: AdaptiveMinMaxTree,
+}
+
+/// The serde layout of a [`BpTree`]. The min-max tree is always stored with `i64` excess values,
+/// independent of the excess type chosen for the tree, which keeps the layout of earlier
+/// versions.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "BpTree")]
+struct SerdeBpTree<V, T> {
+    vec: V,
+    min_max_tree: T,
+}
+
+#[cfg(feature = "serde")]
+impl<const BLOCK_SIZE: usize> serde::Serialize for BpTree<BLOCK_SIZE> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerdeBpTree {
+            vec: &self.vec,
+            min_max_tree: self.min_max_tree.to_wide(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const BLOCK_SIZE: usize> serde::Deserialize<'de> for BpTree<BLOCK_SIZE> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let SerdeBpTree { vec, min_max_tree } =
+            SerdeBpTree::<RsVec, MinMaxTree64>::deserialize(deserializer)?;
+        let min_max_tree =
+            AdaptiveMinMaxTree::try_from_wide(&min_max_tree, vec.len()).ok_or_else(|| {
+                serde::de::Error::custom("excess value doesn't fit into the excess type")
+            })?;
+        Ok(Self { vec, min_max_tree })
+    }
+}
+
+/// An overview of the shape and size of a [`BpTree`], as returned by [`BpTree::stats`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BpTreeStats {
+    /// The number of nodes in the tree
+    pub num_nodes: usize,
+
+    /// The number of leaves in the tree
+    pub num_leaves: usize,
+
+    /// The maximum depth of any node, where the root has depth 0. Zero for an empty tree.
+    pub height: u64,
+
+    /// The maximum number of children of any node
+    pub max_degree: usize,
+
+    /// The average number of children of the inner nodes (i.e. excluding leaves).
+    /// Zero if the tree has no inner nodes.
+    pub avg_degree: f64,
+
+    /// The number of bytes used on the heap by the tree, as reported by [`BpTree::heap_size`]
+    pub heap_size: usize,
+}
+
+/// A breadth-first (level-order) numbering of the nodes of a [`BpTree`], as returned by
+/// [`BpTree::bfs_order`].
+/// The nodes are numbered level by level starting with the root, and from left to right within
+/// each level.
+///
+/// Both directions of the mapping are stored explicitly, so the structure needs two words per
+/// node, and both queries take constant time.
+#[derive(Clone, Debug)]
+pub struct BfsOrder<'a, const BLOCK_SIZE: usize = DEFAULT_BLOCK_SIZE> {
+    tree: &'a BpTree<BLOCK_SIZE>,
+
+    /// Node handles in breadth-first order
+    nodes: Vec<usize>,
+
+    /// Breadth-first rank of each node, indexed by the pre-order index of the node
+    ranks: Vec<usize>,
+}
+
+impl<const BLOCK_SIZE: usize> BfsOrder<'_, BLOCK_SIZE> {
+    /// Returns the index of `node` in the breadth-first order of the tree.
+    /// The root has rank 0.
+    ///
+    /// If the node is not a valid node handle, the result is meaningless.
+    #[must_use]
+    pub fn bfs_rank(&self, node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle) -> usize {
+        self.ranks[self.tree.node_index(node)]
+    }
+
+    /// Returns the node with the given index in the breadth-first order of the tree,
+    /// or `None` if the tree has at most `rank` nodes.
+    #[must_use]
+    pub fn bfs_select(&self, rank: usize) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        self.nodes.get(rank).copied()
+    }
+
+    /// Returns the number of nodes in the numbering.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the numbering is empty, i.e. whether the tree is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
 }
 
 impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
     /// Construct a new `BpTree` from a given bit vector.
     #[must_use]
     pub fn from_bit_vector(bv: BitVec) -> Self {
-        let min_max_tree = MinMaxTree::excess_tree(&bv, BLOCK_SIZE);
+        let min_max_tree = AdaptiveMinMaxTree::excess_tree(&bv, BLOCK_SIZE);
+        let vec = bv.into();
+        Self { vec, min_max_tree }
+    }
+
+    /// Construct a new `BpTree` from a given bit vector like [`from_bit_vector`], but scan the
+    /// blocks of the bit vector in parallel on rayon's global thread pool.
+    /// This speeds up the construction of very large trees, the result is identical.
+    ///
+    /// This function is only available with the `rayon` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector_parallel(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    /// assert_eq!(tree.size(), 3);
+    /// ```
+    ///
+    /// [`from_bit_vector`]: BpTree::from_bit_vector
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn from_bit_vector_parallel(bv: BitVec) -> Self {
+        let min_max_tree = AdaptiveMinMaxTree::excess_tree_parallel(&bv, BLOCK_SIZE);
         let vec = bv.into();
         Self { vec, min_max_tree }
     }
 
+    /// Construct a new `BpTree` from a given bit vector, and validate that it is a balanced
+    /// parenthesis expression.
+    /// The validation happens during the construction pass of [`from_bit_vector`], so it adds
+    /// little overhead, but the unchecked constructor remains faster.
+    ///
+    /// # Errors
+    /// Returns an [`ExcessError`] with the position of the first closing parenthesis without a
+    /// matching opening parenthesis, or of the first opening parenthesis that is never closed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    /// use vers_vecs::trees::bp::ExcessError;
+    ///
+    /// let tree = BpTree::<4>::try_from_bit_vector(BitVec::from_bits(&[1, 1, 0, 0])).unwrap();
+    /// assert_eq!(tree.size(), 2);
+    ///
+    /// let error = BpTree::<4>::try_from_bit_vector(BitVec::from_bits(&[1, 0, 0, 1])).unwrap_err();
+    /// assert_eq!(error, ExcessError::UnmatchedClose { position: 2 });
+    ///
+    /// let error = BpTree::<4>::try_from_bit_vector(BitVec::from_bits(&[1, 0, 1, 1, 0])).unwrap_err();
+    /// assert_eq!(error, ExcessError::UnclosedOpen { position: 2, excess: 1 });
+    /// ```
+    ///
+    /// [`from_bit_vector`]: BpTree::from_bit_vector
+    pub fn try_from_bit_vector(bv: BitVec) -> Result<Self, ExcessError> {
+        let min_max_tree = AdaptiveMinMaxTree::try_excess_tree(&bv, BLOCK_SIZE)?;
+        let vec = bv.into();
+        Ok(Self { vec, min_max_tree })
+    }
+
+    /// Construct a new `BpTree` from an undirected tree given as a list of edges between the nodes
+    /// `0..num_nodes`, rooted at `root`.
+    /// The children of each node are ordered by the order in which their edges appear in the list.
+    ///
+    /// Returns the tree and a mapping from nodes of the tree to the input nodes, which is indexed
+    /// by the pre-order index of the nodes (see [`node_index`]).
+    ///
+    /// # Errors
+    /// Returns an error if `root` or an endpoint of an edge is not a node,
+    /// if the edges contain a cycle (including self-loops and duplicate edges),
+    /// or if not all nodes are reachable from `root`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BpTree, Tree};
+    ///
+    /// let (tree, nodes) = BpTree::<4>::from_adjacency(4, &[(2, 0), (1, 2), (3, 2)], 2).unwrap();
+    ///
+    /// // node 2 is the root with children 0, 1, 3
+    /// assert_eq!(nodes, vec![2, 0, 1, 3]);
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(nodes[tree.node_index(root)], 2);
+    /// assert_eq!(tree.children(root).map(|c| nodes[tree.node_index(c)]).collect::<Vec<_>>(), vec![0, 1, 3]);
+    ///
+    /// // a cycle is rejected
+    /// assert!(BpTree::<4>::from_adjacency(3, &[(0, 1), (1, 2), (2, 0)], 0).is_err());
+    /// ```
+    ///
+    /// [`node_index`]: Tree::node_index
+    pub fn from_adjacency(
+        num_nodes: usize,
+        edges: &[(usize, usize)],
+        root: usize,
+    ) -> Result<(Self, Vec<usize>), String> {
+        if root >= num_nodes {
+            return Err(format!("root {root} is not one of the {num_nodes} nodes"));
+        }
+        if let Some(&(u, v)) = edges
+            .iter()
+            .find(|&&(u, v)| u >= num_nodes || v >= num_nodes)
+        {
+            return Err(format!(
+                "edge ({u}, {v}) is not between two of the {num_nodes} nodes"
+            ));
+        }
+
+        // adjacency lists of all nodes in one array, storing the index of each edge to distinguish
+        // the edge to the parent from duplicate edges
+        let mut offsets = vec![0; num_nodes + 1];
+        for &(u, v) in edges {
+            offsets[u + 1] += 1;
+            offsets[v + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut adjacency = vec![(0, 0); 2 * edges.len()];
+        let mut next = offsets.clone();
+        for (edge, &(u, v)) in edges.iter().enumerate() {
+            adjacency[next[u]] = (v, edge);
+            next[u] += 1;
+            adjacency[next[v]] = (u, edge);
+            next[v] += 1;
+        }
+
+        // depth-first traversal, emitting the parenthesis expression
+        let mut visited = vec![false; num_nodes];
+        let mut nodes = Vec::with_capacity(num_nodes);
+        let mut bv = BitVec::with_capacity(2 * num_nodes);
+
+        // stack of nodes, the edge to their parent, and the position of their next neighbor
+        let mut stack = vec![(root, usize::MAX, offsets[root])];
+        visited[root] = true;
+        nodes.push(root);
+        bv.append_bit(OPEN_PAREN);
+
+        while let Some(&(node, parent_edge, position)) = stack.last() {
+            if position == offsets[node + 1] {
+                bv.append_bit(CLOSE_PAREN);
+                stack.pop();
+                continue;
+            }
+
+            stack.last_mut().unwrap().2 += 1;
+            let (neighbor, edge) = adjacency[position];
+            if edge == parent_edge {
+                continue;
+            }
+            if visited[neighbor] {
+                return Err(format!("the edges contain a cycle through node {neighbor}"));
+            }
+
+            visited[neighbor] = true;
+            nodes.push(neighbor);
+            bv.append_bit(OPEN_PAREN);
+            stack.push((neighbor, edge, offsets[neighbor]));
+        }
+
+        if let Some(unreachable) = visited.iter().position(|&v| !v) {
+            return Err(format!(
+                "node {unreachable} is not reachable from the root {root}"
+            ));
+        }
+
+        Ok((Self::from_bit_vector(bv), nodes))
+    }
+
+    /// Construct the Cartesian tree of a sequence of values, in the variant that preserves the
+    /// order of the values in the pre-order of the tree (also known as 2d-Min-Heap):
+    /// The parent of the value at index `i` is the nearest preceding value that is not larger
+    /// than it, or an artificial root if no such value exists.
+    /// The tree thus has `values.len() + 1` nodes, and the value at index `i` corresponds to the
+    /// node with pre-order index `i + 1` (see [`node_handle`]).
+    ///
+    /// Since the lowest common ancestor of two values is their range minimum (or the child of it
+    /// that precedes the second value), this tree is the basis of succinct range minimum queries.
+    ///
+    /// The parenthesis expression is built in linear time using a stack of the values on the
+    /// rightmost path of the tree: before the node of each value is opened, the nodes of all
+    /// larger values on the stack are closed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BpTree, Tree};
+    ///
+    /// let tree = BpTree::<4>::cartesian_tree(&[3, 1, 4, 1, 5]);
+    /// let value_node = |i: usize| tree.node_handle(i + 1);
+    ///
+    /// // the first 1 is the parent of 4 and the second 1, and 3 is a child of the artificial root
+    /// assert_eq!(tree.parent(value_node(0)), tree.root());
+    /// assert_eq!(tree.parent(value_node(1)), tree.root());
+    /// assert_eq!(tree.parent(value_node(2)), Some(value_node(1)));
+    /// assert_eq!(tree.parent(value_node(3)), Some(value_node(1)));
+    /// assert_eq!(tree.parent(value_node(4)), Some(value_node(3)));
+    /// ```
+    ///
+    /// [`node_handle`]: Tree::node_handle
+    #[must_use]
+    pub fn cartesian_tree(values: &[i64]) -> Self {
+        let mut bv = BitVec::with_capacity(2 * (values.len() + 1));
+        let mut stack = Vec::new();
+
+        bv.append_bit(OPEN_PAREN);
+        for &value in values {
+            while stack.last().is_some_and(|&top| top > value) {
+                stack.pop();
+                bv.append_bit(CLOSE_PAREN);
+            }
+            stack.push(value);
+            bv.append_bit(OPEN_PAREN);
+        }
+        for _ in 0..=stack.len() {
+            bv.append_bit(CLOSE_PAREN);
+        }
+
+        Self::from_bit_vector(bv)
+    }
+
     /// Search for a position where the excess relative to the starting `index` is `relative_excess`.
     /// Returns `None` if no such position exists.
     /// The initial position is never considered in the search.
@@ -163,8 +487,8 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
     /// - `relative_excess`: The desired relative excess value.
     pub fn fwd_search(&self, index: usize, mut relative_excess: i64) -> Option<usize> {
         // check for greater than or equal length minus one, because the last element
-        // won't ever have a result from fwd_search
-        if index >= (self.vec.len() - 1) {
+        // won't ever have a result from fwd_search. Saturate to handle empty trees
+        if index >= self.vec.len().saturating_sub(1) {
             return None;
         }
 
@@ -246,6 +570,56 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
         Err(())
     }
 
+    /// Perform multiple forward searches, each given as a pair of starting index and relative
+    /// excess, with the same semantics as [`fwd_search`].
+    /// Returns the results in the order of the queries.
+    ///
+    /// The blocks in which the searches start and resolve are preprocessed into prefix-excess
+    /// indices, which are kept in `cache` and reused by later queries in the same blocks.
+    ///
+    /// [`fwd_search`]: BpTree::fwd_search
+    #[allow(dead_code)] // no caller: repeated `fwd_search` with the lookup tables is faster
+    fn fwd_search_multi_cached(
+        &self,
+        queries: &[(usize, i64)],
+        cache: &mut BlockExcessCache,
+    ) -> Vec<Option<usize>> {
+        queries
+            .iter()
+            .map(|&(index, relative_excess)| {
+                if index + 1 >= self.vec.len() {
+                    return None;
+                }
+
+                let block_index = (index + 1) / BLOCK_SIZE;
+                let offset = index + 1 - block_index * BLOCK_SIZE;
+                match self
+                    .block_excess(cache, block_index)
+                    .fwd_search(offset, relative_excess)
+                {
+                    Ok(position) => Some(position),
+                    Err(relative_excess) => {
+                        let (block, relative_excess) =
+                            self.min_max_tree.fwd_search(block_index, relative_excess)?;
+                        self.block_excess(cache, block)
+                            .fwd_search(0, relative_excess)
+                            .ok()
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Get the prefix-excess index of the block at `block_index` from the cache.
+    fn block_excess<'a>(
+        &self,
+        cache: &'a mut BlockExcessCache,
+        block_index: usize,
+    ) -> &'a BlockExcess {
+        let start = block_index * BLOCK_SIZE;
+        cache.get(&self.vec, start, min(BLOCK_SIZE, self.vec.len() - start))
+    }
+
     /// Search for a position where the excess relative to the starting `index` is `relative_excess`.
     /// Returns `None` if no such position exists.
     /// The initial position is never considered in the search.
@@ -333,6 +707,106 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
         Err(())
     }
 
+    /// Search the min-max-tree for the first block after the block containing `start_bit` that
+    /// contains a position with the given excess.
+    /// This exposes the block-level part of [`fwd_search`], so custom traversals can reuse the
+    /// logarithmic tree search and scan the resulting block themselves.
+    ///
+    /// The `relative_excess` is relative to the end of the block containing `start_bit`, i.e. if
+    /// the query seeks excess `x` relative to some position and the excess between that position
+    /// and the end of its block is `y`, the caller passes `x - y`.
+    /// The block containing `start_bit` itself is never returned.
+    ///
+    /// Returns the index of the found block and the residual relative excess, which is relative to
+    /// the start of that block.
+    /// Returns `None` if `start_bit` is out of bounds, if it lies in the last block (which may be
+    /// incomplete), or if no later block reaches the excess.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 1, 0, 0, 0, 0]));
+    /// assert_eq!(tree.forward_excess_block(0, -1), Some((1, -1)));
+    /// assert_eq!(tree.forward_excess_block(0, -5), None);
+    /// assert_eq!(tree.forward_excess_block(5, -1), None);
+    /// ```
+    ///
+    /// [`fwd_search`]: BpTree::fwd_search
+    #[must_use]
+    pub fn forward_excess_block(
+        &self,
+        start_bit: usize,
+        relative_excess: i64,
+    ) -> Option<(usize, i64)> {
+        // the last block has no successor, and the padding of the min-max-tree behind it would
+        // report spurious matches for a relative excess of zero
+        if start_bit >= self.vec.len()
+            || start_bit / BLOCK_SIZE == (self.vec.len() - 1) / BLOCK_SIZE
+        {
+            return None;
+        }
+
+        self.min_max_tree
+            .fwd_search(start_bit / BLOCK_SIZE, relative_excess)
+    }
+
+    /// Search the min-max-tree for the closest block before the block containing `start_bit` that
+    /// contains a position with the given excess.
+    /// This exposes the block-level part of [`bwd_search`], and is the symmetric counterpart to
+    /// [`forward_excess_block`].
+    ///
+    /// The `relative_excess` is relative to the start of the block containing `start_bit`, i.e. if
+    /// the query seeks excess `x` relative to some position and the excess between the start of
+    /// its block and that position is `y`, the caller passes `x + y`.
+    /// The block containing `start_bit` itself is never returned.
+    ///
+    /// Returns the index of the found block and the residual relative excess, which is relative to
+    /// the end of that block.
+    /// Returns `None` if `start_bit` is out of bounds, if it lies in the first block, or if no
+    /// earlier block reaches the excess.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 1, 0, 0, 0, 0]));
+    /// assert_eq!(tree.backward_excess_block(7, -1), Some((0, -1)));
+    /// assert_eq!(tree.backward_excess_block(7, -5), None);
+    /// assert_eq!(tree.backward_excess_block(2, -1), None);
+    /// ```
+    ///
+    /// [`bwd_search`]: BpTree::bwd_search
+    /// [`forward_excess_block`]: BpTree::forward_excess_block
+    #[must_use]
+    pub fn backward_excess_block(
+        &self,
+        start_bit: usize,
+        relative_excess: i64,
+    ) -> Option<(usize, i64)> {
+        if start_bit >= self.vec.len() {
+            return None;
+        }
+
+        let block = start_bit / BLOCK_SIZE;
+        if relative_excess == 0 {
+            // the excess is reached at the start of the current block, which the min-max-tree
+            // reports as a match in the preceding block. Scan that block explicitly instead
+            if block == 0 {
+                return None;
+            }
+
+            let mut relative_excess = 0;
+            return match self.bwd_search_block(block * BLOCK_SIZE, block - 1, &mut relative_excess)
+            {
+                Ok(_) => Some((block - 1, 0)),
+                Err(()) => self.min_max_tree.bwd_search(block - 1, relative_excess),
+            };
+        }
+
+        self.min_max_tree.bwd_search(block, relative_excess)
+    }
+
     /// Find the position of the matching closing parenthesis for the opening parenthesis at `index`.
     /// If the bit at `index` is not an opening parenthesis, the result is meaningless.
     /// If there is no matching closing parenthesis, `None` is returned.
@@ -382,7 +856,80 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
     #[must_use]
     pub fn excess(&self, index: usize) -> i64 {
         debug_assert!(index < self.vec.len(), "Index out of bounds");
-        self.vec.rank1(index + 1) as i64 - self.vec.rank0(index + 1) as i64
+        self.vec.excess_at(index + 1)
+    }
+
+    /// Find the leftmost position in `range` at which the [`excess`] is minimal among all
+    /// positions in the range, and return it together with the minimum excess.
+    /// Returns `None` if the range is empty or exceeds the parenthesis expression.
+    ///
+    /// For two nodes `u < v`, the minimum excess in `u..v` is the depth of their lowest common
+    /// ancestor (plus one), so this is the primitive behind lowest common ancestor queries.
+    ///
+    /// The partial blocks at the boundaries of the range are scanned, and the block containing
+    /// the minimum of the blocks in between is found using the min-max tree,
+    /// so the query takes `O(BLOCK_SIZE + log n)` time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.range_min_excess(1..6), Some((4, 1)));
+    /// assert_eq!(tree.range_min_excess(1..3), Some((1, 2)));
+    /// assert_eq!(tree.range_min_excess(3..3), None);
+    /// ```
+    ///
+    /// [`excess`]: BpTree::excess
+    #[must_use]
+    pub fn range_min_excess(&self, range: Range<usize>) -> Option<(usize, i64)> {
+        if range.is_empty() || range.end > self.vec.len() {
+            return None;
+        }
+
+        let first_block = range.start / BLOCK_SIZE;
+        let last_block = (range.end - 1) / BLOCK_SIZE;
+        if first_block == last_block {
+            return self.scan_min_excess(range);
+        }
+
+        let mut candidates =
+            vec![self.scan_min_excess(range.start..(first_block + 1) * BLOCK_SIZE)];
+        if first_block + 1 < last_block {
+            let block = self
+                .min_max_tree
+                .range_argmin_block(first_block + 1, last_block);
+            candidates.push(self.scan_min_excess(block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE));
+        }
+        candidates.push(self.scan_min_excess(last_block * BLOCK_SIZE..range.end));
+
+        // candidates are ordered by position, so the first minimum is the leftmost one
+        candidates.into_iter().flatten().reduce(|best, candidate| {
+            if candidate.1 < best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Scan the bits in `range` for the leftmost position of minimum excess, and return it
+    /// together with the excess at that position, or `None` if the range is empty.
+    fn scan_min_excess(&self, range: Range<usize>) -> Option<(usize, i64)> {
+        let mut excess = self.vec.excess_at(range.start);
+        let mut best: Option<(usize, i64)> = None;
+        for position in range {
+            excess += if self.vec.get_unchecked(position) == OPEN_PAREN {
+                1
+            } else {
+                -1
+            };
+            if best.is_none_or(|(_, min)| excess < min) {
+                best = Some((position, excess));
+            }
+        }
+
+        best
     }
 
     /// Iterate over the nodes of the tree.
@@ -412,69 +959,1307 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
         self.vec.iter1()
     }
 
-    /// Iterate over the nodes of a valid tree in depth-first (post-)order.
-    /// This is slower than the pre-order iteration.
+    /// Iterate over the nodes of the tree in depth-first (pre-)order, together with their depth.
+    /// The depth is computed from a running excess while scanning the parenthesis expression,
+    /// which is faster than calling [`depth`] for each node.
+    /// The root has depth 0.
     ///
-    /// # Panics
-    /// The iterator may panic at any point if the parenthesis expression is unbalanced.
-    pub fn dfs_post_iter(
+    /// If the tree is unbalanced, the iterator behaves like [`dfs_iter`], and the depth of each
+    /// node is the same value [`depth`] reports for it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let nodes = tree.preorder_with_depth().collect::<Vec<_>>();
+    /// assert_eq!(nodes, vec![(0, 0), (1, 1), (2, 2), (5, 1)]);
+    /// ```
+    ///
+    /// [`depth`]: Tree::depth
+    /// [`dfs_iter`]: BpTree::dfs_iter
+    pub fn preorder_with_depth(
         &self,
-    ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
-        self.vec.iter0().map(|n| self.open(n).unwrap())
+    ) -> impl Iterator<Item = (<BpTree<BLOCK_SIZE> as Tree>::NodeHandle, u64)> + use<'_, BLOCK_SIZE>
+    {
+        // excess up to and including the last yielded node, and the position of that node
+        let mut last = (0i64, None);
+        self.vec.iter1().map(move |node| {
+            let (excess, last_node) = last;
+            #[allow(clippy::cast_possible_wrap)] // vector length is far below 2^63
+            let closing = match last_node {
+                Some(last_node) => (node - last_node - 1) as i64,
+                None => node as i64,
+            };
+            let excess = excess - closing + 1;
+            last = (excess, Some(node));
+
+            let depth: u64 = excess.try_into().unwrap_or(0);
+            (node, depth.saturating_sub(1))
+        })
     }
 
-    /// Iterate over a subtree rooted at `node` in depth-first (pre-)order.
-    /// The iteration starts with the node itself.
+    /// Iterate over the leaves of the tree in depth-first (pre-)order, i.e. from left to right.
+    /// The leaves are found by scanning the parenthesis expression for `()` patterns,
+    /// so iterating over all leaves takes linear time in the size of the tree.
     ///
-    /// Calling this method on an invalid node handle, or an unbalanced parenthesis expression,
-    /// will produce an iterator over an unspecified subset of nodes.
-    pub fn subtree_iter(
+    /// If the tree is unbalanced, the iterator returns all open parentheses immediately followed
+    /// by a closing parenthesis.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.leaves().collect::<Vec<_>>(), vec![2, 5]);
+    /// ```
+    pub fn leaves(
         &self,
-        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
     ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
-        debug_assert!(
-            self.vec.get(node) == Some(OPEN_PAREN),
-            "Node handle is invalid"
-        );
-
-        let index = self.vec.rank1(node);
-        let close = self.close(node).unwrap_or(node);
-        let subtree_size = self.vec.rank1(close) - index;
-
-        self.vec.iter1().skip(index).take(subtree_size)
+        self.vec
+            .iter1()
+            .filter(|&node| self.vec.get(node + 1) == Some(CLOSE_PAREN))
     }
 
-    /// Iterate over a subtree rooted at `node` in depth-first (post-)order.
-    /// This is slower than the pre-order iteration.
-    /// The iteration ends with the node itself.
+    /// Returns the number of leaves in the tree.
+    /// The leaves are counted by iterating over [`leaves`], so this takes linear time in the size
+    /// of the tree.
     ///
-    /// # Panics
-    /// Calling this method on an invalid node handle, or an unbalanced parenthesis expression,
-    /// will produce an iterator over an unspecified subset of nodes, or panic either during
-    /// construction or iteration.
-    pub fn subtree_post_iter(
-        &self,
-        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
-    ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
-        debug_assert!(
-            self.vec.get(node) == Some(OPEN_PAREN),
-            "Node handle is invalid"
-        );
-
-        let index = self.vec.rank0(node);
-        let close = self.close(node).unwrap_or(node);
-        let subtree_size = self.vec.rank0(close) + 1 - index;
-
-        self.vec
-            .iter0()
-            .skip(index)
-            .take(subtree_size)
-            .map(|n| self.open(n).unwrap())
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.num_leaves(), 2);
+    /// ```
+    ///
+    /// [`leaves`]: BpTree::leaves
+    #[must_use]
+    pub fn num_leaves(&self) -> usize {
+        self.leaves().count()
     }
 
-    /// Iterate over the children of a node in the tree.
-    /// The iterator yields the children in the order they appear in the parenthesis expression.
-    /// If the node is a leaf, the iterator is empty.
+    /// Returns the number of internal nodes in the tree, i.e. the nodes with at least one child.
+    /// Together with [`num_leaves`], this sums up to the [`size`] of the tree.
+    /// This takes linear time in the size of the tree.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.num_internal(), 2);
+    /// ```
+    ///
+    /// [`num_leaves`]: BpTree::num_leaves
+    /// [`size`]: Tree::size
+    #[must_use]
+    pub fn num_internal(&self) -> usize {
+        self.size() - self.num_leaves()
+    }
+
+    /// Compute the depth of the lowest common ancestor of each pair of adjacent leaves, in the
+    /// left-to-right order of [`leaves`].
+    /// The result has one entry less than the tree has leaves, and is empty if the tree has at most
+    /// one leaf.
+    /// This is the LCP array of the leaves if the tree is a trie.
+    ///
+    /// The depths are computed in a single scan of the parenthesis expression, which tracks the
+    /// minimum excess between adjacent leaves.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// // the first two leaves share the child of the root, the third one only the root
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+    ///     1, 1, 1, 0, 1, 0, 0, 1, 0, 0,
+    /// ]));
+    /// assert_eq!(tree.leaf_lca_depths(), vec![1, 0]);
+    /// ```
+    ///
+    /// [`leaves`]: BpTree::leaves
+    #[must_use]
+    pub fn leaf_lca_depths(&self) -> Vec<u64> {
+        let mut depths = Vec::new();
+        let mut excess = 0i64;
+        let mut previous = CLOSE_PAREN;
+
+        // minimum excess since the end of the last leaf
+        let mut min_excess = None;
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                excess += 1;
+            } else {
+                excess -= 1;
+            }
+            min_excess = min_excess.map(|min: i64| min.min(excess));
+
+            if bit == CLOSE_PAREN && previous == OPEN_PAREN {
+                // the minimum excess is reached inside the lowest common ancestor of the leaves
+                if let Some(min) = min_excess {
+                    let depth: u64 = min.try_into().unwrap_or(0);
+                    depths.push(depth.saturating_sub(1));
+                }
+                min_excess = Some(excess);
+            }
+            previous = bit;
+        }
+
+        depths
+    }
+
+    /// Returns the deepest node whose subtree contains all leaves with indices in
+    /// `leaf_lo..=leaf_hi`, where the leaves are indexed from left to right as in [`leaves`].
+    /// If `leaf_lo == leaf_hi`, this is the leaf itself, otherwise it is the lowest common
+    /// ancestor of the two leaves.
+    /// Returns `None` if `leaf_lo > leaf_hi` or if the tree has at most `leaf_hi` leaves.
+    ///
+    /// The leaves are located by scanning the parenthesis expression for leaves,
+    /// which takes linear time in the size of the tree.
+    /// The common ancestor is the parent of the node whose closing parenthesis has the minimum
+    /// excess between the two leaves (see [`range_min_excess`]).
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.smallest_enclosing_node(0, 1), Some(1));
+    /// assert_eq!(tree.smallest_enclosing_node(1, 2), Some(0));
+    /// assert_eq!(tree.smallest_enclosing_node(2, 2), Some(7));
+    /// assert_eq!(tree.smallest_enclosing_node(2, 3), None);
+    /// ```
+    ///
+    /// [`leaves`]: BpTree::leaves
+    /// [`range_min_excess`]: BpTree::range_min_excess
+    #[must_use]
+    pub fn smallest_enclosing_node(
+        &self,
+        leaf_lo: usize,
+        leaf_hi: usize,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        if leaf_lo > leaf_hi {
+            return None;
+        }
+
+        let mut leaves = self.leaves();
+        let first = leaves.nth(leaf_lo)?;
+        if leaf_lo == leaf_hi {
+            return Some(first);
+        }
+        let last = leaves.nth(leaf_hi - leaf_lo - 1)?;
+
+        let (min_position, _) = self.range_min_excess(first..last + 1)?;
+        self.parent(self.open(min_position)?)
+    }
+
+    /// Compute the depth of every node, packed into a [`BitVec`] in pre-order of the nodes.
+    /// Each depth is encoded in the same number of bits, which is the number of bits needed to
+    /// represent the [height] of the tree (but at least one), and which is returned alongside the
+    /// packed depths.
+    /// The depth of a node with pre-order index `i` (see [`node_index`]) can then be read with
+    /// [`unpack_element(i, bits)`][BitVec::unpack_element], which avoids the rank query of
+    /// [`depth`] in workloads with many depth lookups.
+    ///
+    /// The depths are computed in a single scan of the parenthesis expression.
+    /// An empty tree returns an empty vector.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let (profile, bits) = tree.depth_profile();
+    ///
+    /// assert_eq!(bits, 2);
+    /// assert_eq!(profile.unpack_element(2, bits), Some(2));
+    /// assert_eq!(profile.unpack_element(3, bits), Some(1));
+    /// ```
+    ///
+    /// [height]: BpTree::height
+    /// [`node_index`]: Tree::node_index
+    /// [`depth`]: Tree::depth
+    #[must_use]
+    pub fn depth_profile(&self) -> (BitVec, usize) {
+        let height = self.height().unwrap_or(0);
+        let bits = (u64::BITS - height.leading_zeros()).max(1) as usize;
+
+        let mut profile = BitVec::with_capacity(self.vec.rank1(self.vec.len()) * bits);
+        let mut excess = 0i64;
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                let depth = u64::try_from(excess).unwrap_or(0);
+                profile.append_bits(depth, bits);
+                excess += 1;
+            } else {
+                excess -= 1;
+            }
+        }
+
+        (profile, bits)
+    }
+
+    /// Count the nodes on each level of the tree.
+    /// The result is indexed by depth, so the first entry is the number of roots (one for a valid
+    /// non-empty tree), and the length of the result is the height of the tree plus one.
+    /// An empty tree returns an empty vector.
+    ///
+    /// The counts are computed in a single scan of the parenthesis expression.
+    ///
+    /// If the tree is unbalanced, nodes are counted at the depth reported by [`depth`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.level_sizes(), vec![1, 2, 1]);
+    /// ```
+    ///
+    /// [`depth`]: Tree::depth
+    #[must_use]
+    pub fn level_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        let mut excess = 0i64;
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                excess += 1;
+                let depth = usize::try_from(excess).unwrap_or(0).saturating_sub(1);
+                if depth >= sizes.len() {
+                    sizes.resize(depth + 1, 0);
+                }
+                sizes[depth] += 1;
+            } else {
+                excess -= 1;
+            }
+        }
+
+        sizes
+    }
+
+    /// Compute the breadth-first (level-order) numbering of the nodes, which maps each node to
+    /// its index in a level-by-level traversal of the tree and back.
+    /// This numbering is used to embed trees into heap-like layouts.
+    ///
+    /// Since the nodes of each level appear from left to right in the parenthesis expression,
+    /// no queue is needed: the first node of each level is numbered after all nodes of the
+    /// previous levels (see [`level_sizes`]), and a second scan numbers the nodes of each level
+    /// consecutively.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let order = tree.bfs_order();
+    /// assert_eq!(order.bfs_rank(0), 0);
+    /// assert_eq!(order.bfs_rank(2), 3);
+    /// assert_eq!(order.bfs_rank(5), 2);
+    /// assert_eq!(order.bfs_select(1), Some(1));
+    /// ```
+    ///
+    /// [`level_sizes`]: BpTree::level_sizes
+    #[must_use]
+    pub fn bfs_order(&self) -> BfsOrder<'_, BLOCK_SIZE> {
+        // rank of the next node on each level
+        let mut next_rank = self
+            .level_sizes()
+            .iter()
+            .scan(0, |offset, &size| {
+                let start = *offset;
+                *offset += size;
+                Some(start)
+            })
+            .collect::<Vec<_>>();
+
+        let mut nodes = vec![0; self.size()];
+        let mut ranks = Vec::with_capacity(self.size());
+        let mut excess = 0i64;
+        for (position, bit) in self.vec.iter().enumerate() {
+            if bit == OPEN_PAREN {
+                excess += 1;
+                let depth = usize::try_from(excess).unwrap_or(0).saturating_sub(1);
+                let rank = next_rank[depth];
+                next_rank[depth] += 1;
+                nodes[rank] = position;
+                ranks.push(rank);
+            } else {
+                excess -= 1;
+            }
+        }
+
+        BfsOrder {
+            tree: self,
+            nodes,
+            ranks,
+        }
+    }
+
+    /// Returns the ancestor of `leaf` at the given absolute depth, i.e. the representative of the
+    /// cluster of nodes below that depth which contains `leaf`.
+    /// The root has depth 0, and a node at depth `depth(leaf)` is the leaf itself.
+    /// Returns `None` if `depth` is larger than the depth of `leaf`.
+    ///
+    /// The method works for inner nodes as well, but is primarily intended for leaves.
+    /// It runs in `O(log n)` time using [`level_ancestor`].
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.ancestor_of_leaf_at_depth(2, 0), Some(0));
+    /// assert_eq!(tree.ancestor_of_leaf_at_depth(2, 1), Some(1));
+    /// assert_eq!(tree.ancestor_of_leaf_at_depth(2, 2), Some(2));
+    /// assert_eq!(tree.ancestor_of_leaf_at_depth(2, 3), None);
+    /// ```
+    ///
+    /// [`level_ancestor`]: LevelTree::level_ancestor
+    #[must_use]
+    pub fn ancestor_of_leaf_at_depth(
+        &self,
+        leaf: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        depth: u64,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(leaf) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let leaf_depth = self.depth(leaf);
+        if depth > leaf_depth {
+            return None;
+        }
+
+        self.level_ancestor(leaf, leaf_depth - depth)
+    }
+
+    /// Returns a bit vector with one bit per node, indexed by the pre-order [`node_index`] of the
+    /// nodes, in which exactly the bits of the proper ancestors of `node` are set.
+    /// The node itself is not included, so the number of set bits is the [`depth`] of the node,
+    /// and the mask of the root is empty.
+    ///
+    /// The masks of different nodes can be combined with the masking operations of [`BitVec`],
+    /// for example to compute common ancestors.
+    /// The ancestors are visited by repeated [`parent`] queries, so this takes `O(n + d log n)`
+    /// time, where `d` is the depth of the node.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.ancestor_mask(2), BitVec::from_bits(&[1, 1, 0, 0]));
+    /// assert_eq!(tree.ancestor_mask(0), BitVec::from_bits(&[0, 0, 0, 0]));
+    /// ```
+    ///
+    /// [`node_index`]: Tree::node_index
+    /// [`depth`]: Tree::depth
+    /// [`parent`]: Tree::parent
+    #[must_use]
+    pub fn ancestor_mask(&self, node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle) -> BitVec {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let mut mask = BitVec::from_zeros(self.size());
+        let mut current = node;
+        while let Some(parent) = self.parent(current) {
+            mask.set_unchecked(self.node_index(parent), 1);
+            current = parent;
+        }
+
+        mask
+    }
+
+    /// Returns the lowest ancestor of `node` that is marked in `marks`, or `None` if no ancestor
+    /// is marked.
+    /// The node itself counts as its own ancestor, so if `node` is marked, it is returned.
+    ///
+    /// The bit vector `marks` contains one bit per node, indexed by the pre-order
+    /// [`node_index`] of the nodes. Nodes beyond the length of `marks` are unmarked.
+    ///
+    /// The ancestors are visited by repeated [`parent`] queries, so this takes `O(d log n)` time,
+    /// where `d` is the distance between `node` and the returned ancestor.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, RsVec};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let marks = RsVec::from_bit_vec(BitVec::from_bits(&[1, 0, 1, 0]));
+    /// assert_eq!(tree.lowest_marked_ancestor(2, &marks), Some(2));
+    /// assert_eq!(tree.lowest_marked_ancestor(1, &marks), Some(0));
+    /// assert_eq!(tree.lowest_marked_ancestor(5, &marks), Some(0));
+    /// ```
+    ///
+    /// [`node_index`]: Tree::node_index
+    /// [`parent`]: Tree::parent
+    #[must_use]
+    pub fn lowest_marked_ancestor(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        marks: &RsVec,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let mut current = node;
+        loop {
+            if marks.get(self.node_index(current)) == Some(1) {
+                return Some(current);
+            }
+            current = self.parent(current)?;
+        }
+    }
+
+    /// List the paths from the root to each leaf of the tree, in depth-first order of the leaves.
+    /// Each path starts with the root and ends with the leaf.
+    /// An empty tree returns an empty vector.
+    ///
+    /// The paths are computed in a single scan of the parenthesis expression, maintaining the path
+    /// to the current node, which is copied at every leaf.
+    /// This takes `O(num_leaves * height)` time and space, so the method is intended for small
+    /// trees.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.root_to_leaf_paths(), vec![vec![0, 1, 2], vec![0, 5]]);
+    /// ```
+    #[must_use]
+    pub fn root_to_leaf_paths(&self) -> Vec<Vec<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle>> {
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        let mut previous = CLOSE_PAREN;
+        for (position, bit) in self.vec.iter().enumerate() {
+            if bit == OPEN_PAREN {
+                path.push(position);
+            } else {
+                // an opening parenthesis directly followed by a closing one is a leaf
+                if previous == OPEN_PAREN {
+                    paths.push(path.clone());
+                }
+                path.pop();
+            }
+            previous = bit;
+        }
+
+        paths
+    }
+
+    /// Returns the number of nodes shared by the paths from the root to `u` and to `v`, including
+    /// the root and the lowest common ancestor of both nodes.
+    /// This is the depth of the lowest common ancestor plus one, so it is `depth(u) + 1` if `u`
+    /// and `v` are equal, and 1 if their only common ancestor is the root.
+    ///
+    /// If neither node is an ancestor of the other, the lowest common ancestor is the parent of
+    /// the node whose closing parenthesis has the minimum excess between both nodes
+    /// (see [`range_min_excess`]), so the query takes `O(BLOCK_SIZE + log n)` time.
+    ///
+    /// If either node is not a valid node handle, or the tree is unbalanced, the result is
+    /// meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.common_prefix_length(2, 4), 2);
+    /// assert_eq!(tree.common_prefix_length(2, 7), 1);
+    /// assert_eq!(tree.common_prefix_length(2, 2), 3);
+    /// ```
+    ///
+    /// [`range_min_excess`]: BpTree::range_min_excess
+    #[must_use]
+    pub fn common_prefix_length(
+        &self,
+        u: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        v: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> usize {
+        debug_assert!(
+            self.vec.get(u) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+        debug_assert!(
+            self.vec.get(v) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let (first, last) = (min(u, v), max(u, v));
+        let lca = if self.close(first).is_none_or(|close| close > last) {
+            // the first node encloses the last one (or both are the same)
+            first
+        } else {
+            self.range_min_excess(first..last)
+                .and_then(|(min_position, _)| self.parent(self.open(min_position)?))
+                .unwrap_or(first)
+        };
+
+        // the depth is bounded by the number of nodes
+        #[allow(clippy::cast_possible_truncation)]
+        let shared = self.depth(lca) as usize + 1;
+        shared
+    }
+
+    /// Compute the Euler tour of the tree, i.e. the sequence of node handles visited by a
+    /// depth-first traversal that records a node when it is entered and each time the traversal
+    /// returns to it from a child.
+    /// Each node appears once more than it has children, so the tour of a tree with `n` nodes has
+    /// `2n - 1` entries, beginning and ending with the root. An empty tree returns an empty vector.
+    ///
+    /// The tour is computed in a single scan of the parenthesis expression, using a stack of
+    /// the currently open nodes.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.euler_tour(), vec![0, 1, 2, 1, 0, 5, 0]);
+    /// ```
+    #[must_use]
+    pub fn euler_tour(&self) -> Vec<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        let mut tour = Vec::with_capacity(self.vec.len().saturating_sub(1));
+        let mut open_nodes = Vec::new();
+        for (position, bit) in self.vec.iter().enumerate() {
+            if bit == OPEN_PAREN {
+                tour.push(position);
+                open_nodes.push(position);
+            } else {
+                open_nodes.pop();
+                if let Some(&parent) = open_nodes.last() {
+                    tour.push(parent);
+                }
+            }
+        }
+
+        tour
+    }
+
+    /// Returns the distance between the first occurrences of the nodes `u` and `v` in the
+    /// [`euler_tour`] of the tree, without materializing the tour.
+    ///
+    /// Every parenthesis except the closing parenthesis of the root contributes exactly one
+    /// entry to the tour, so the first occurrence of a node is at the index of its opening
+    /// parenthesis, and the distance is the difference between the node handles.
+    /// This runs in constant time.
+    ///
+    /// If the tree is unbalanced or a forest, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.euler_distance(2, 5), 3);
+    /// assert_eq!(tree.euler_distance(5, 1), 4);
+    /// ```
+    ///
+    /// [`euler_tour`]: BpTree::euler_tour
+    #[must_use]
+    pub fn euler_distance(
+        &self,
+        u: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        v: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> usize {
+        debug_assert!(
+            self.vec.get(u) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+        debug_assert!(
+            self.vec.get(v) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        u.abs_diff(v)
+    }
+
+    /// Returns the width of the tree, i.e. the maximum number of nodes on any level of the tree,
+    /// or zero if the tree is empty.
+    /// This is computed in a single scan of the parenthesis expression, using one counter per
+    /// level (see [`level_sizes`]).
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.width(), 2);
+    /// ```
+    ///
+    /// [`level_sizes`]: BpTree::level_sizes
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.level_sizes().into_iter().max().unwrap_or(0)
+    }
+
+    /// Returns whether the tree is a binary tree, i.e. whether every node has at most two
+    /// children. An empty tree is a binary tree.
+    ///
+    /// This is checked in a single scan of the parenthesis expression, using a stack of child
+    /// counts of the currently open nodes.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert!(tree.is_binary());
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 0, 1, 0, 0]));
+    /// assert!(!tree.is_binary());
+    /// ```
+    #[must_use]
+    pub fn is_binary(&self) -> bool {
+        let mut child_counts: Vec<u8> = Vec::new();
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                if let Some(count) = child_counts.last_mut() {
+                    *count += 1;
+                    if *count > 2 {
+                        return false;
+                    }
+                }
+                child_counts.push(0);
+            } else {
+                child_counts.pop();
+            }
+        }
+
+        true
+    }
+
+    /// Count the nodes in the subtree rooted at `node` whose depth relative to `node` is at most
+    /// `max_relative_depth`, including `node` itself.
+    /// With `max_relative_depth = 0`, this returns 1, and for depths at least as large as the
+    /// height of the subtree, it returns the [`subtree_size`].
+    ///
+    /// The count is computed by a scan over the parenthesis expression of the subtree,
+    /// so it takes linear time in the size of the subtree.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.subtree_count_below_depth(0, 0), 1);
+    /// assert_eq!(tree.subtree_count_below_depth(0, 1), 3);
+    /// assert_eq!(tree.subtree_count_below_depth(0, 5), 4);
+    /// ```
+    ///
+    /// [`subtree_size`]: SubtreeSize::subtree_size
+    #[must_use]
+    pub fn subtree_count_below_depth(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        max_relative_depth: usize,
+    ) -> usize {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let end = self.close(node).unwrap_or(self.vec.len());
+
+        // the relative depth of the last opened node
+        let mut depth = 0usize;
+        let mut count = 1;
+        for i in node + 1..end {
+            if self.vec.get_unchecked(i) == OPEN_PAREN {
+                depth += 1;
+                if depth <= max_relative_depth {
+                    count += 1;
+                }
+            } else {
+                depth = depth.saturating_sub(1);
+            }
+        }
+
+        count
+    }
+
+    /// Returns the child of `node` with the largest subtree, i.e. the heavy child used in
+    /// heavy-path decompositions.
+    /// If multiple children have subtrees of the same maximum size, the first of them is returned.
+    /// Returns `None` if `node` is a leaf.
+    ///
+    /// This queries the subtree size of every child, so it takes `O(d log n)` time for a node with
+    /// `d` children.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 0, 0]));
+    /// assert_eq!(tree.heaviest_child(0), Some(3));
+    /// assert_eq!(tree.heaviest_child(1), None);
+    /// ```
+    #[must_use]
+    pub fn heaviest_child(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let mut heaviest: Option<(usize, usize)> = None;
+        for child in self.children(node) {
+            let size = self.subtree_size(child).unwrap_or(0);
+            // only replace on strictly larger subtrees, so ties go to the first child
+            if heaviest.is_none_or(|(_, max_size)| size > max_size) {
+                heaviest = Some((child, size));
+            }
+        }
+
+        heaviest.map(|(child, _)| child)
+    }
+
+    /// Returns the diameter of the tree, i.e. the number of edges on the longest path between
+    /// any two nodes. The diameter of an empty tree and of a single node is 0.
+    ///
+    /// The longest path passes through a topmost node, from which it descends into its two
+    /// highest subtrees. The diameter is computed in a single scan of the parenthesis expression,
+    /// using a stack with the two largest heights of the child subtrees of each open node.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.diameter(), 3);
+    /// ```
+    #[must_use]
+    pub fn diameter(&self) -> usize {
+        let mut diameter = 0;
+
+        // the two largest heights (plus one for the edge to the open node) of the children of
+        // each open node
+        let mut heights: Vec<(usize, usize)> = Vec::new();
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                heights.push((0, 0));
+            } else if let Some((first, second)) = heights.pop() {
+                diameter = max(diameter, first + second);
+                if let Some(parent) = heights.last_mut() {
+                    if first + 1 > parent.0 {
+                        *parent = (first + 1, parent.0);
+                    } else if first + 1 > parent.1 {
+                        parent.1 = first + 1;
+                    }
+                }
+            }
+        }
+
+        diameter
+    }
+
+    /// Returns a centroid of the tree, i.e. a node whose removal splits the tree into components
+    /// with at most half of the nodes each, or `None` if the tree is empty.
+    /// Centroids are the basis of centroid decompositions for divide-and-conquer algorithms on
+    /// trees.
+    ///
+    /// The centroid is found by walking down from the root towards the
+    /// [heaviest child][BpTree::heaviest_child] as long as its subtree contains more than half of
+    /// the nodes.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// // a path of three nodes
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 0]));
+    /// assert_eq!(tree.centroid(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn centroid(&self) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        let half = self.size() / 2;
+        let mut node = self.root()?;
+        while let Some(child) = self.heaviest_child(node) {
+            if self.subtree_size(child).unwrap_or(0) <= half {
+                break;
+            }
+            node = child;
+        }
+
+        Some(node)
+    }
+
+    /// Compute a heavy-path decomposition of the tree.
+    /// The decomposition partitions the nodes into chains, such that each chain is a downward path
+    /// which continues from each node to its [heaviest child][BpTree::heaviest_child].
+    /// Any path from the root to a leaf then traverses at most `log2(n)` light edges, i.e. edges
+    /// between different chains, which allows answering path queries in `O(log n)` chain steps.
+    ///
+    /// Returns the chain id of each node, indexed by the pre-order index of the nodes
+    /// (see [`node_index`]).
+    /// Chains are numbered in pre-order of their topmost nodes, so the root is in chain 0.
+    /// An empty tree returns an empty vector.
+    ///
+    /// The decomposition is computed in two scans of the parenthesis expression, and needs
+    /// `O(n)` additional memory.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// // the root has a leaf child and a child with two leaf children
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 1, 0, 0, 0]));
+    /// assert_eq!(tree.heavy_path_decomposition(), vec![0, 1, 0, 0, 2]);
+    /// ```
+    ///
+    /// [`node_index`]: Tree::node_index
+    #[must_use]
+    pub fn heavy_path_decomposition(&self) -> Vec<usize> {
+        let num_nodes = self.vec.rank1(self.vec.len());
+
+        // first scan: find the pre-order index of the heavy child of each node.
+        // The stack holds the pre-order index and position of each open node, and the index and
+        // subtree size of its heaviest child so far.
+        let mut heavy_child = vec![None; num_nodes];
+        let mut stack: Vec<(usize, usize, Option<usize>, usize)> = Vec::new();
+        let mut index = 0;
+        for (position, bit) in self.vec.iter().enumerate() {
+            if bit == OPEN_PAREN {
+                stack.push((index, position, None, 0));
+                index += 1;
+            } else if let Some((node, open, heaviest, _)) = stack.pop() {
+                heavy_child[node] = heaviest;
+                let size = (position - open).div_ceil(2);
+                if let Some(parent) = stack.last_mut() {
+                    // only replace on strictly larger subtrees, so ties go to the first child
+                    if parent.2.is_none() || size > parent.3 {
+                        parent.2 = Some(node);
+                        parent.3 = size;
+                    }
+                }
+            }
+        }
+
+        // second scan: continue the chain of the parent for heavy children, and start a new
+        // chain for all other nodes
+        let mut chains = vec![0; num_nodes];
+        let mut num_chains = 0;
+        let mut stack = Vec::new();
+        let mut index = 0;
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                chains[index] = match stack.last() {
+                    Some(&parent) if heavy_child[parent] == Some(index) => chains[parent],
+                    _ => {
+                        num_chains += 1;
+                        num_chains - 1
+                    }
+                };
+                stack.push(index);
+                index += 1;
+            } else {
+                stack.pop();
+            }
+        }
+
+        chains
+    }
+
+    /// Returns the ranges of the parenthesis expression covered by the subtrees of the children
+    /// of `node`, in order of the children.
+    /// Each range starts at the opening parenthesis of a child and ends after its closing
+    /// parenthesis, so the ranges are disjoint and together cover the interior of `node`.
+    /// This is meant to partition the work on a tree, for example to process the subtrees in
+    /// parallel (see also [`subtree_bits`]).
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is
+    /// meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.child_ranges(0), vec![1..5, 5..7]);
+    /// assert!(tree.child_ranges(2).is_empty());
+    /// ```
+    ///
+    /// [`subtree_bits`]: BpTree::subtree_bits
+    #[must_use]
+    pub fn child_ranges(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> Vec<Range<usize>> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        self.children(node)
+            .map(|child| child..self.close(child).map_or(self.vec.len(), |close| close + 1))
+            .collect()
+    }
+
+    /// Returns a copy of the parenthesis expression of the subtree rooted at `node`, i.e. the bits
+    /// from the opening parenthesis of `node` up to and including its closing parenthesis.
+    /// The result is a balanced parenthesis expression that can be used to build an independent
+    /// tree with [`from_bit_vector`], in which `node` is the root.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is
+    /// meaningless. If `node` has no closing parenthesis, the bits up to the end of the
+    /// expression are returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let bits = tree.subtree_bits(1);
+    /// assert_eq!(bits, BitVec::from_bits(&[1, 1, 0, 0]));
+    ///
+    /// let subtree = BpTree::<4>::from_bit_vector(bits);
+    /// assert_eq!(subtree.size(), 2);
+    /// ```
+    ///
+    /// [`from_bit_vector`]: BpTree::from_bit_vector
+    #[must_use]
+    pub fn subtree_bits(&self, node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle) -> BitVec {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let end = self.close(node).map_or(self.vec.len(), |close| close + 1);
+        let mut bits = BitVec::with_capacity(end - node);
+        for start in (node..end).step_by(64) {
+            let len = min(64, end - start);
+            bits.append_bits(self.vec.get_bits_unchecked(start, len), len);
+        }
+
+        bits
+    }
+
+    /// Returns whether the subtrees rooted at `u` and `v` have the same shape as ordered trees,
+    /// i.e. whether they are isomorphic when the order of the children matters.
+    ///
+    /// Two ordered trees are isomorphic exactly if their parenthesis expressions are equal,
+    /// so the [`subtree_bits`] of both nodes are compared word by word, without copying them.
+    /// If the subtrees have different sizes, this is detected before comparing any bits.
+    ///
+    /// If a node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert!(tree.subtrees_isomorphic(1, 5));
+    /// assert!(!tree.subtrees_isomorphic(1, 9));
+    /// ```
+    ///
+    /// [`subtree_bits`]: BpTree::subtree_bits
+    #[must_use]
+    pub fn subtrees_isomorphic(
+        &self,
+        u: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        v: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> bool {
+        debug_assert!(
+            self.vec.get(u) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+        debug_assert!(
+            self.vec.get(v) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let u_end = self.close(u).map_or(self.vec.len(), |close| close + 1);
+        let v_end = self.close(v).map_or(self.vec.len(), |close| close + 1);
+        if u_end - u != v_end - v {
+            return false;
+        }
+
+        (0..u_end - u).step_by(64).all(|offset| {
+            let len = min(64, u_end - u - offset);
+            self.vec.get_bits_unchecked(u + offset, len)
+                == self.vec.get_bits_unchecked(v + offset, len)
+        })
+    }
+
+    /// Returns the node following `node` in depth-first pre-order, i.e. its first child if it has
+    /// children, otherwise the next sibling of the closest ancestor-or-self that has one.
+    /// Returns `None` if `node` is the last node in pre-order.
+    ///
+    /// Since the nodes are stored in pre-order, this is the next opening parenthesis after `node`,
+    /// which is found with one rank and one select query instead of navigating the tree.
+    ///
+    /// If the node is not a valid node handle, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.preorder_next(0), Some(1));
+    /// assert_eq!(tree.preorder_next(2), Some(5));
+    /// assert_eq!(tree.preorder_next(5), None);
+    /// ```
+    #[must_use]
+    pub fn preorder_next(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let rank = self.vec.rank1_inclusive(node);
+        if rank >= self.vec.rank1(self.vec.len()) {
+            return None;
+        }
+
+        Some(self.vec.select1(rank))
+    }
+
+    /// Returns the node preceding `node` in depth-first pre-order, i.e. the last node in the
+    /// subtree of its previous sibling if it has one, otherwise its parent.
+    /// Returns `None` if `node` is the root.
+    ///
+    /// Since the nodes are stored in pre-order, this is the previous opening parenthesis before
+    /// `node`, which is found with one rank and one select query.
+    ///
+    /// If the node is not a valid node handle, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.preorder_prev(5), Some(2));
+    /// assert_eq!(tree.preorder_prev(1), Some(0));
+    /// assert_eq!(tree.preorder_prev(0), None);
+    /// ```
+    #[must_use]
+    pub fn preorder_prev(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let rank = self.vec.rank1(node);
+        if rank == 0 {
+            return None;
+        }
+
+        Some(self.vec.select1(rank - 1))
+    }
+
+    /// Returns the rank of a node in the depth-first post-order of the tree, i.e. the number of
+    /// nodes whose subtree is completed before the subtree of `node`.
+    /// This is the number of closing parentheses before the closing parenthesis of the node.
+    /// The root has the largest post-order rank.
+    /// Use [`postorder_select`] to find a node by its post-order rank.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.postorder_rank(2), 0);
+    /// assert_eq!(tree.postorder_rank(1), 1);
+    /// assert_eq!(tree.postorder_rank(5), 2);
+    /// assert_eq!(tree.postorder_rank(0), 3);
+    /// ```
+    ///
+    /// [`postorder_select`]: BpTree::postorder_select
+    #[must_use]
+    pub fn postorder_rank(&self, node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle) -> usize {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        self.vec.rank0(self.close(node).unwrap_or(self.vec.len()))
+    }
+
+    /// Returns the node with the given rank in the depth-first post-order of the tree,
+    /// or `None` if the rank is not smaller than the number of nodes.
+    /// This is the inverse of [`postorder_rank`].
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.postorder_select(0), Some(2));
+    /// assert_eq!(tree.postorder_select(3), Some(0));
+    /// assert_eq!(tree.postorder_select(4), None);
+    /// ```
+    ///
+    /// [`postorder_rank`]: BpTree::postorder_rank
+    #[must_use]
+    pub fn postorder_select(
+        &self,
+        rank: usize,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        if rank >= self.vec.rank0(self.vec.len()) {
+            return None;
+        }
+
+        self.open(self.vec.select0(rank))
+    }
+
+    /// Returns the `k`-th node (0-indexed) in the depth-first (pre-)order traversal of the subtree
+    /// rooted at `node`, or `None` if the subtree has at most `k` nodes.
+    /// For `k = 0`, this returns `node` itself.
+    ///
+    /// Since the nodes of a subtree are contiguous in pre-order, this is a [`node_handle`] query
+    /// at the offset `k` from the [`node_index`] of `node`, which takes constant time
+    /// after the subtree size is computed.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.subtree_preorder_select(1, 0), Some(1));
+    /// assert_eq!(tree.subtree_preorder_select(1, 1), Some(2));
+    /// assert_eq!(tree.subtree_preorder_select(1, 2), None);
+    /// ```
+    ///
+    /// [`node_handle`]: Tree::node_handle
+    /// [`node_index`]: Tree::node_index
+    #[must_use]
+    pub fn subtree_preorder_select(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        k: usize,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        if k >= self.subtree_size(node)? {
+            return None;
+        }
+
+        Some(self.node_handle(self.node_index(node) + k))
+    }
+
+    /// Iterate over the nodes of a valid tree in depth-first (post-)order.
+    /// This is slower than the pre-order iteration.
+    ///
+    /// # Panics
+    /// The iterator may panic at any point if the parenthesis expression is unbalanced.
+    pub fn dfs_post_iter(
+        &self,
+    ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
+        self.vec.iter0().map(|n| self.open(n).unwrap())
+    }
+
+    /// Iterate over a subtree rooted at `node` in depth-first (pre-)order.
+    /// The iteration starts with the node itself.
+    ///
+    /// Calling this method on an invalid node handle, or an unbalanced parenthesis expression,
+    /// will produce an iterator over an unspecified subset of nodes.
+    pub fn subtree_iter(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let index = self.vec.rank1(node);
+        let close = self.close(node).unwrap_or(node);
+        let subtree_size = self.vec.rank1(close) - index;
+
+        self.vec.iter1().skip(index).take(subtree_size)
+    }
+
+    /// Iterate over a subtree rooted at `node` in depth-first (post-)order.
+    /// This is slower than the pre-order iteration.
+    /// The iteration ends with the node itself.
+    ///
+    /// # Panics
+    /// Calling this method on an invalid node handle, or an unbalanced parenthesis expression,
+    /// will produce an iterator over an unspecified subset of nodes, or panic either during
+    /// construction or iteration.
+    pub fn subtree_post_iter(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+    ) -> impl Iterator<Item = <BpTree<BLOCK_SIZE> as Tree>::NodeHandle> + use<'_, BLOCK_SIZE> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let index = self.vec.rank0(node);
+        let close = self.close(node).unwrap_or(node);
+        let subtree_size = self.vec.rank0(close) + 1 - index;
+
+        self.vec
+            .iter0()
+            .skip(index)
+            .take(subtree_size)
+            .map(|n| self.open(n).unwrap())
+    }
+
+    /// Iterate over the children of a node in the tree.
+    /// The iterator yields the children in the order they appear in the parenthesis expression.
+    /// If the node is a leaf, the iterator is empty.
     /// If the node is not a valid node handle, or the tree is unbalanced,
     /// the iterator will produce an unspecified subset of the tree's nodes.
     pub fn children(
@@ -506,6 +2291,258 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
         ChildrenIter::<BLOCK_SIZE, false>::new(self, node)
     }
 
+    /// Find the nearest proper ancestor of `node` that has at least `min_degree` children.
+    /// With `min_degree = 2`, this is the nearest branching point above the node.
+    /// Returns `None` if no ancestor up to and including the root has enough children.
+    ///
+    /// The query climbs the tree one [`parent`] at a time, so it takes `O(d log n)` time,
+    /// where `d` is the distance to the result.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// [`parent`]: Tree::parent
+    #[must_use]
+    pub fn nearest_branching_ancestor(
+        &self,
+        node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle,
+        min_degree: usize,
+    ) -> Option<<BpTree<BLOCK_SIZE> as Tree>::NodeHandle> {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        let mut current = self.parent(node)?;
+        loop {
+            if self.children(current).take(min_degree).count() >= min_degree {
+                return Some(current);
+            }
+            current = self.parent(current)?;
+        }
+    }
+
+    /// Returns the in-order rank of a node, i.e. the number of nodes that precede it in an
+    /// in-order traversal of the tree, interpreting the first child of each node as its left
+    /// subtree and its second child as its right subtree.
+    ///
+    /// Nodes with more than two children are handled by treating the first child as the left
+    /// subtree and all remaining children as the right subtree,
+    /// i.e. the in-order traversal of a node visits its first child's subtree, then the node itself,
+    /// and then the subtrees of all remaining children from left to right.
+    /// Note that a node with exactly one child therefore only has a left subtree.
+    ///
+    /// The query climbs the tree from the node to the root, so it takes `O(d log n)` time,
+    /// where `d` is the depth of the node.
+    ///
+    /// If the node is not a valid node handle, or the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// // a root with a left and a right child
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    /// assert_eq!(tree.inorder_rank(1), 0);
+    /// assert_eq!(tree.inorder_rank(0), 1);
+    /// assert_eq!(tree.inorder_rank(3), 2);
+    /// ```
+    #[must_use]
+    pub fn inorder_rank(&self, node: <BpTree<BLOCK_SIZE> as Tree>::NodeHandle) -> usize {
+        debug_assert!(
+            self.vec.get(node) == Some(OPEN_PAREN),
+            "Node handle is invalid"
+        );
+
+        // the nodes of the left subtree precede the node in in-order, but not in pre-order
+        let left_subtree = self
+            .first_child(node)
+            .and_then(|child| self.subtree_size(child))
+            .unwrap_or(0);
+
+        // ancestors of which the node is in the left subtree precede it in pre-order, but not in
+        // in-order. The first child of a node immediately follows it in the parenthesis expression.
+        let mut left_ancestors = 0;
+        let mut current = node;
+        while let Some(parent) = self.parent(current) {
+            if parent + 1 == current {
+                left_ancestors += 1;
+            }
+            current = parent;
+        }
+
+        (self.vec.rank1(node) + left_subtree).saturating_sub(left_ancestors)
+    }
+
+    /// Returns the height of the tree, i.e. the maximum [`depth`] of any node, or `None` if the
+    /// tree is empty.
+    /// The height is read from the root of the min-max tree, so this takes constant time.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// assert_eq!(tree.height(), Some(2));
+    /// ```
+    ///
+    /// [`depth`]: Tree::depth
+    #[must_use]
+    pub fn height(&self) -> Option<u64> {
+        let (_, _, max) = self.min_max_tree.root_stats()?;
+        let max: u64 = max.try_into().unwrap_or(0);
+        Some(max.saturating_sub(1))
+    }
+
+    /// Returns the deepest node of the tree together with its depth, or `None` if the tree is
+    /// empty.
+    /// If multiple nodes have the maximum depth, the first of them in depth-first order is
+    /// returned, which is the end of the first longest run of opening parentheses reaching
+    /// the maximum excess.
+    ///
+    /// The maximum excess is stored in the root of the min-max tree, and its first position
+    /// is found with a forward search, so this takes `O(log n)` time.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 0, 0]));
+    /// assert_eq!(tree.deepest_descent(), Some((4, 2)));
+    /// ```
+    #[must_use]
+    pub fn deepest_descent(&self) -> Option<(<BpTree<BLOCK_SIZE> as Tree>::NodeHandle, u64)> {
+        let (_, _, max) = self.min_max_tree.root_stats()?;
+        if max < 1 {
+            return None;
+        }
+
+        // the excess after the root is 1, so search for the remaining excess from there
+        let node = if max == 1 {
+            0
+        } else {
+            self.fwd_search(0, max - 1)?
+        };
+
+        Some((node, max.unsigned_abs() - 1))
+    }
+
+    /// Compute an overview of the tree's shape and size in a single scan of the parenthesis
+    /// expression.
+    /// This is meant for tuning and debugging and takes linear time in the size of the tree.
+    ///
+    /// If the tree is unbalanced, the result is meaningless.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.num_nodes, 4);
+    /// assert_eq!(stats.num_leaves, 2);
+    /// assert_eq!(stats.height, 2);
+    /// assert_eq!(stats.max_degree, 2);
+    /// assert_eq!(stats.avg_degree, 1.5);
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> BpTreeStats {
+        let mut num_leaves = 0;
+        let mut max_degree = 0;
+
+        // number of children of each node on the path from the root to the current node
+        let mut degrees = Vec::new();
+        let mut previous = CLOSE_PAREN;
+        for bit in self.vec.iter() {
+            if bit == OPEN_PAREN {
+                if let Some(degree) = degrees.last_mut() {
+                    *degree += 1;
+                }
+                degrees.push(0);
+            } else {
+                if previous == OPEN_PAREN {
+                    num_leaves += 1;
+                }
+                if let Some(degree) = degrees.pop() {
+                    max_degree = max(max_degree, degree);
+                }
+            }
+            previous = bit;
+        }
+
+        let num_nodes = self.size();
+        let num_inner_nodes = num_nodes - num_leaves;
+
+        #[allow(clippy::cast_precision_loss)] // only precise up to 2^52 nodes
+        let avg_degree = if num_inner_nodes == 0 {
+            0.0
+        } else {
+            (num_nodes - 1) as f64 / num_inner_nodes as f64
+        };
+
+        BpTreeStats {
+            num_nodes,
+            num_leaves,
+            height: self.height().unwrap_or(0),
+            max_degree,
+            avg_degree,
+            heap_size: self.heap_size(),
+        }
+    }
+
+    /// Create a new tree in which the children of every node are in reverse order.
+    /// The parenthesis expression of the mirrored tree is the reversed expression of this tree
+    /// with opening and closing parentheses swapped, so it is computed in linear time without
+    /// traversing the tree.
+    /// The node that corresponds to a node `v` in the mirrored tree is located at the mirrored
+    /// position of the closing parenthesis of `v`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// // the root has a leaf as its first child and a node with one child as its second child
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 0, 0]));
+    /// let mirrored = tree.mirror();
+    ///
+    /// // the node with one child is now the first child of the root
+    /// assert_eq!(mirrored.first_child(0), Some(1));
+    /// assert!(!mirrored.is_leaf(1));
+    /// assert!(tree.structurally_eq(&mirrored.mirror()));
+    /// ```
+    #[must_use]
+    pub fn mirror(&self) -> Self {
+        let mut bv = BitVec::with_capacity(self.vec.len());
+        for i in (0..self.vec.len()).rev() {
+            bv.append_bit(self.vec.get_unchecked(i) ^ 1);
+        }
+        Self::from_bit_vector(bv)
+    }
+
+    /// Check whether this tree and `other` have the same shape, i.e. whether their parenthesis
+    /// expressions are equal.
+    /// The block sizes of the trees may differ, and the support structures are not compared.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let bits = BitVec::from_bits(&[1, 1, 0, 1, 0, 0]);
+    /// let tree = BpTree::<4>::from_bit_vector(bits.clone());
+    /// let other = BpTree::<64>::from_bit_vector(bits);
+    /// assert!(tree.structurally_eq(&other));
+    /// ```
+    #[must_use]
+    pub fn structurally_eq<const OTHER_BLOCK_SIZE: usize>(
+        &self,
+        other: &BpTree<OTHER_BLOCK_SIZE>,
+    ) -> bool {
+        self.vec == other.vec
+    }
+
     /// Transform the tree into a [`RsVec`] containing the balanced parenthesis expression.
     /// This consumes the tree and returns the underlying bit vector with the rank and select
     /// support structure.
@@ -536,12 +2573,184 @@ impl<const BLOCK_SIZE: usize> BpTree<BLOCK_SIZE> {
         self.vec
     }
 
+    /// Flip the parenthesis at `index`, turning an opening parenthesis into a closing one and
+    /// vice versa.
+    /// The min-max-tree is updated in place by rescanning only the block containing `index`,
+    /// but the rank and select structures of the parenthesis vector are rebuilt, so this still
+    /// takes linear time.
+    /// It is cheaper than converting the tree with [`into_parentheses_vec`] and rebuilding it
+    /// with [`from_bit_vector`], however.
+    ///
+    /// Since a single flip changes the excess of the expression by two, the tree is unbalanced
+    /// afterward, unless another flip restores the balance.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// let mut tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    /// assert_eq!(tree.size(), 3);
+    ///
+    /// // nest the second child of the root into the first one
+    /// tree.flip_bit(2);
+    /// tree.flip_bit(3);
+    /// assert_eq!(tree.size(), 3);
+    /// assert_eq!(tree.close(1), Some(4));
+    /// ```
+    ///
+    /// [`into_parentheses_vec`]: BpTree::into_parentheses_vec
+    /// [`from_bit_vector`]: BpTree::from_bit_vector
+    pub fn flip_bit(&mut self, index: usize) {
+        assert!(index < self.vec.len(), "Index out of bounds");
+
+        let vec = std::mem::replace(&mut self.vec, RsVec::from_bit_vec(BitVec::new()));
+        let mut bit_vec = vec.into_bit_vec();
+        bit_vec.flip_bit(index);
+        self.min_max_tree.update_bit(
+            &bit_vec,
+            BLOCK_SIZE,
+            index,
+            bit_vec.is_bit_set_unchecked(index),
+        );
+        self.vec = RsVec::from_bit_vec(bit_vec);
+    }
+
+    /// Write the tree to `writer` in a compact binary layout, so it can be restored with
+    /// [`read_from`] without rebuilding the min-max tree.
+    /// The layout consists of the block size and the number of bits as little-endian `u64`,
+    /// followed by the parenthesis expression as little-endian `u64` limbs, and the min-max tree
+    /// (its number of nodes followed by the `total`, `min`, and `max` excess of each node in level
+    /// order, all little-endian).
+    /// The layout is independent of the `serde` feature.
+    ///
+    /// Every value is written individually, so `writer` should be buffered.
+    ///
+    /// # Errors
+    /// Returns any error that occurs while writing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree, Tree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    /// let mut bytes = Vec::new();
+    /// tree.write_to(&mut bytes).unwrap();
+    ///
+    /// let restored = BpTree::<4>::read_from(&mut bytes.as_slice()).unwrap();
+    /// assert_eq!(restored.size(), 4);
+    /// assert_eq!(restored.close(1), Some(4));
+    ///
+    /// // the block size must match
+    /// assert!(BpTree::<8>::read_from(&mut bytes.as_slice()).is_err());
+    /// ```
+    ///
+    /// [`read_from`]: BpTree::read_from
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(BLOCK_SIZE as u64).to_le_bytes())?;
+        writer.write_all(&(self.vec.len() as u64).to_le_bytes())?;
+        for start in (0..self.vec.len()).step_by(64) {
+            let limb = self
+                .vec
+                .get_bits_unchecked(start, min(64, self.vec.len() - start));
+            writer.write_all(&limb.to_le_bytes())?;
+        }
+        self.min_max_tree.serialize_to(writer)
+    }
+
+    /// Read a tree written by [`write_to`] from `reader`.
+    /// The rank and select structures of the parenthesis expression are rebuilt, but the min-max
+    /// tree is restored as it was written.
+    ///
+    /// Every value is read individually, so `reader` should be buffered.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`InvalidData`] if the block size of the input doesn't match
+    /// `BLOCK_SIZE`, or if the min-max tree is malformed or doesn't match the length of the
+    /// expression, and any error that occurs while reading (including [`UnexpectedEof`] if the
+    /// input is truncated).
+    ///
+    /// [`write_to`]: BpTree::write_to
+    /// [`InvalidData`]: io::ErrorKind::InvalidData
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut buffer = [0u8; 8];
+        let mut read_value = |reader: &mut R| -> io::Result<u64> {
+            reader.read_exact(&mut buffer)?;
+            Ok(u64::from_le_bytes(buffer))
+        };
+
+        if read_value(reader)? != BLOCK_SIZE as u64 {
+            return Err(invalid("block size doesn't match"));
+        }
+        let len = usize::try_from(read_value(reader)?)
+            .map_err(|_| invalid("length exceeds the address space"))?;
+
+        // the length is not trusted for preallocation, as it may be corrupted
+        let mut limbs = Vec::new();
+        for _ in 0..len.div_ceil(64) {
+            limbs.push(read_value(reader)?);
+        }
+        let vec = RsVec::from_raw_words(limbs, len);
+
+        let min_max_tree = AdaptiveMinMaxTree::deserialize_from(reader, len)?;
+        let num_leaves = match min_max_tree.num_nodes() {
+            0 => 0,
+            num_nodes => MinMaxTree64::num_leaves_of(num_nodes).unwrap_or_default(),
+        };
+        if num_leaves != len.div_ceil(BLOCK_SIZE) {
+            return Err(invalid(
+                "min-max tree doesn't match the length of the expression",
+            ));
+        }
+
+        Ok(Self { vec, min_max_tree })
+    }
+
     /// Returns the number of bytes used on the heap for this tree. This does not include
     /// allocated space that is not used (e.g. by the allocation behavior of `Vec`).
     #[must_use]
     pub fn heap_size(&self) -> usize {
         self.vec.heap_size() + self.min_max_tree.heap_size()
     }
+
+    /// Returns the number of blocks of `BLOCK_SIZE` bits the parenthesis expression is split into,
+    /// which is the number of leaves of the internal min-max tree.
+    /// The last block may be incomplete. Returns 0 for an empty tree.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0, 1, 0]));
+    /// assert_eq!(tree.num_blocks(), 3);
+    /// assert_eq!(BpTree::<4>::from_bit_vector(BitVec::new()).num_blocks(), 0);
+    /// ```
+    #[must_use]
+    pub fn num_blocks(&self) -> usize {
+        self.min_max_tree.num_blocks()
+    }
+
+    /// Returns the number of levels of the internal min-max tree, including the level of the
+    /// leaves. Searches that leave a block walk at most this many levels up and down the tree.
+    /// Returns 0 for an empty tree.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use vers_vecs::{BitVec, BpTree};
+    ///
+    /// let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0, 1, 0]));
+    /// // three leaves below a complete binary tree of three internal nodes
+    /// assert_eq!(tree.min_max_tree_depth(), 3);
+    /// assert_eq!(BpTree::<4>::from_bit_vector(BitVec::new()).min_max_tree_depth(), 0);
+    /// ```
+    #[must_use]
+    pub fn min_max_tree_depth(&self) -> usize {
+        self.min_max_tree.depth()
+    }
 }
 
 impl<const BLOCK_SIZE: usize> Tree for BpTree<BLOCK_SIZE> {
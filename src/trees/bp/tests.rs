@@ -1,7 +1,33 @@
 use super::*;
+use crate::trees::TreeBuilder;
 use crate::BitVec;
 use rand::rngs::StdRng;
-use rand::{RngCore, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
+
+/// The tree `0 -> 1 -> (2, 4 -> (5, 7 -> (8, 10, 12 -> 13)))`, where nodes are identified by
+/// their opening parenthesis.
+const EXAMPLE_TREE: [u8; 20] = [1, 1, 1, 0, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 0, 0, 0, 0, 0];
+
+/// Build a random tree with `nodes` nodes below a single root by a depth-first walk, which
+/// descends with probability `p` and otherwise returns to the parent.
+fn random_tree<const BLOCK_SIZE: usize>(seed: u8, nodes: usize, p: f64) -> BpTree<BLOCK_SIZE> {
+    let mut rng = StdRng::from_seed([seed; 32]);
+    let mut builder = BpBuilder::<BLOCK_SIZE>::with_capacity(nodes as u64);
+    builder.enter_node();
+    let mut depth = 1;
+    for _ in 1..nodes {
+        while depth > 1 && !rng.gen_bool(p) {
+            builder.leave_node();
+            depth -= 1;
+        }
+        builder.enter_node();
+        depth += 1;
+    }
+    for _ in 0..depth {
+        builder.leave_node();
+    }
+    builder.build().unwrap()
+}
 
 #[test]
 fn test_fwd_search() {
@@ -924,3 +950,1517 @@ fn test_from_padded_bitvec() {
     assert_eq!(tree.fwd_search(0, 2), None);
     assert_eq!(tree.dfs_iter().collect::<Vec<_>>(), vec![0]);
 }
+
+#[test]
+fn test_nearest_branching_ancestor() {
+    // a path of four nodes has no branching ancestor
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 1, 0, 0, 0, 0]));
+    assert_eq!(tree.nearest_branching_ancestor(3, 2), None);
+    assert_eq!(tree.nearest_branching_ancestor(3, 1), Some(2));
+    assert_eq!(tree.nearest_branching_ancestor(0, 1), None);
+
+    // 0 -> 1 -> (2, 4 -> (5, 7 -> (8, 10, 12 -> 13)))
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    assert_eq!(tree.nearest_branching_ancestor(13, 2), Some(7));
+    assert_eq!(tree.nearest_branching_ancestor(13, 3), Some(7));
+    assert_eq!(tree.nearest_branching_ancestor(13, 4), None);
+    assert_eq!(tree.nearest_branching_ancestor(12, 2), Some(7));
+    assert_eq!(tree.nearest_branching_ancestor(7, 2), Some(4));
+    assert_eq!(tree.nearest_branching_ancestor(5, 2), Some(4));
+    assert_eq!(tree.nearest_branching_ancestor(4, 2), Some(1));
+    assert_eq!(tree.nearest_branching_ancestor(1, 2), None);
+    assert_eq!(tree.nearest_branching_ancestor(1, 1), Some(0));
+    assert_eq!(tree.nearest_branching_ancestor(0, 0), None);
+}
+
+#[test]
+fn test_preorder_with_depth() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    let nodes = tree.preorder_with_depth().collect::<Vec<_>>();
+    assert_eq!(nodes[0], (0, 0));
+    assert_eq!(
+        nodes.iter().map(|&(n, _)| n).collect::<Vec<_>>(),
+        tree.dfs_iter().collect::<Vec<_>>()
+    );
+    for (node, depth) in nodes {
+        assert_eq!(
+            depth,
+            tree.depth(node),
+            "depth of node {} is incorrect",
+            node
+        );
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.preorder_with_depth().count(), 0);
+}
+
+#[test]
+fn test_preorder_with_depth_unbalanced() {
+    let mut rng = StdRng::from_seed([2; 32]);
+    let mut bit_vec = BitVec::new();
+    for _ in 0..64 {
+        bit_vec.append_word(rng.next_u64());
+    }
+
+    let tree = BpTree::<32>::from_bit_vector(bit_vec);
+    for (node, depth) in tree.preorder_with_depth() {
+        assert_eq!(
+            depth,
+            tree.depth(node),
+            "depth of node {} is incorrect",
+            node
+        );
+    }
+}
+
+#[test]
+fn test_leaves() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    let leaves = tree.leaves().collect::<Vec<_>>();
+    assert_eq!(leaves, vec![2, 5, 8, 10, 13]);
+    assert!(leaves.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(
+        leaves.len(),
+        tree.dfs_iter().filter(|&n| tree.is_leaf(n)).count()
+    );
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.leaves().count(), 0);
+}
+
+#[test]
+fn test_leaves_randomized() {
+    let mut rng = StdRng::from_seed([3; 32]);
+    let mut bit_vec = BitVec::new();
+    for _ in 0..64 {
+        bit_vec.append_word(rng.next_u64());
+    }
+
+    let tree = BpTree::<32>::from_bit_vector(bit_vec);
+    let leaves = tree.leaves().collect::<Vec<_>>();
+    let expected = tree
+        .dfs_iter()
+        .filter(|&n| tree.is_leaf(n))
+        .collect::<Vec<_>>();
+    assert_eq!(leaves, expected);
+}
+
+#[test]
+fn test_fwd_search_multi() {
+    let mut rng = StdRng::from_seed([5; 32]);
+    let mut bit_vec = BitVec::new();
+    for _ in 0..64 {
+        bit_vec.append_word(rng.next_u64());
+    }
+
+    let tree = BpTree::<128>::from_bit_vector(bit_vec);
+    let mut queries = Vec::new();
+    for index in (0..tree.vec.len() + 2).step_by(3) {
+        for relative_excess in [-3, -1, 1, 2] {
+            queries.push((index, relative_excess));
+        }
+    }
+
+    let expected = queries
+        .iter()
+        .map(|&(index, relative_excess)| tree.fwd_search(index, relative_excess))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tree.fwd_search_multi_cached(&queries, &mut BlockExcessCache::default()),
+        expected
+    );
+
+    // unsorted queries
+    let queries = queries.into_iter().rev().collect::<Vec<_>>();
+    let expected = expected.into_iter().rev().collect::<Vec<_>>();
+    assert_eq!(
+        tree.fwd_search_multi_cached(&queries, &mut BlockExcessCache::default()),
+        expected
+    );
+
+    let tree = BpTree::<128>::from_bit_vector(BitVec::new());
+    assert_eq!(
+        tree.fwd_search_multi_cached(&[(0, -1)], &mut BlockExcessCache::default()),
+        vec![None]
+    );
+}
+
+#[test]
+fn test_fwd_search_multi_reuses_blocks() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 0, 1, 0, 1, 0]));
+
+    // all searches resolve in the first block, so its index is built once
+    let mut cache = BlockExcessCache::default();
+    let results = tree.fwd_search_multi_cached(&[(0, -1), (1, -1), (2, -1), (0, 1)], &mut cache);
+    assert_eq!(results, vec![Some(3), Some(2), Some(3), Some(1)]);
+    assert_eq!(cache.builds(), 1);
+
+    // the cache is kept across calls, and keeps both blocks when alternating between them
+    let results = tree.fwd_search_multi_cached(&[(1, -1), (4, -1), (5, 1), (2, -1)], &mut cache);
+    assert_eq!(results, vec![Some(2), Some(5), Some(6), Some(3)]);
+    assert_eq!(cache.builds(), 2);
+
+    // searches leaving their block keep both the start and the result block cached
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    let mut cache = BlockExcessCache::default();
+    let results = tree.fwd_search_multi_cached(&[(0, -1), (1, -1), (2, -1)], &mut cache);
+    assert_eq!(results, vec![Some(7), Some(4), Some(3)]);
+    assert_eq!(cache.builds(), 2);
+}
+
+#[test]
+fn test_height() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    assert_eq!(tree.height(), tree.dfs_iter().map(|n| tree.depth(n)).max());
+    assert_eq!(tree.height(), Some(5));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.height(), Some(0));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.height(), None);
+}
+
+/// Reference in-order traversal interpreting the first child as the left subtree and all other
+/// children as the right subtree
+fn inorder_reference<const BLOCK_SIZE: usize>(
+    tree: &BpTree<BLOCK_SIZE>,
+    node: usize,
+    order: &mut Vec<usize>,
+) {
+    let mut children = tree.children(node);
+    if let Some(left) = children.next() {
+        inorder_reference(tree, left, order);
+    }
+    order.push(node);
+    for right in children {
+        inorder_reference(tree, right, order);
+    }
+}
+
+#[test]
+fn test_inorder_rank() {
+    // binary tree:
+    //        0
+    //      /   \
+    //     1     9
+    //    / \   /
+    //   2   4 10
+    //      /
+    //     5
+    #[rustfmt::skip]
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 1, 0, 0,
+        0, 1, 1, 0, 0, 0,
+    ]));
+
+    let mut order = Vec::new();
+    inorder_reference(&tree, tree.root().unwrap(), &mut order);
+    assert_eq!(order, vec![2, 1, 5, 4, 0, 10, 9]);
+    for (rank, &node) in order.iter().enumerate() {
+        assert_eq!(tree.inorder_rank(node), rank, "wrong rank for node {node}");
+    }
+
+    // nodes with more than two children
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0,
+    ]));
+    let mut order = Vec::new();
+    inorder_reference(&tree, tree.root().unwrap(), &mut order);
+    for (rank, &node) in order.iter().enumerate() {
+        assert_eq!(tree.inorder_rank(node), rank, "wrong rank for node {node}");
+    }
+}
+
+#[test]
+fn test_stats() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0,
+    ]));
+    let stats = tree.stats();
+
+    assert_eq!(stats.num_nodes, tree.size());
+    assert_eq!(stats.num_leaves, tree.leaves().count());
+    assert_eq!(stats.height, tree.height().unwrap());
+    assert_eq!(
+        stats.max_degree,
+        tree.dfs_iter()
+            .map(|n| tree.children(n).count())
+            .max()
+            .unwrap()
+    );
+    assert_eq!(stats.heap_size, tree.heap_size());
+
+    assert_eq!(stats.num_nodes, 9);
+    assert_eq!(stats.num_leaves, 6);
+    assert_eq!(stats.height, 2);
+    assert_eq!(stats.max_degree, 3);
+    assert!((stats.avg_degree - 8.0 / 3.0).abs() < 1e-9);
+
+    let stats = BpTree::<4>::from_bit_vector(BitVec::new()).stats();
+    assert_eq!(stats.num_nodes, 0);
+    assert_eq!(stats.num_leaves, 0);
+    assert_eq!(stats.height, 0);
+    assert_eq!(stats.max_degree, 0);
+    assert_eq!(stats.avg_degree, 0.0);
+}
+
+#[test]
+fn test_structurally_eq() {
+    let bits = BitVec::from_bits(&[1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0]);
+    let tree = BpTree::<4>::from_bit_vector(bits.clone());
+    let other = BpTree::<512>::from_bit_vector(bits.clone());
+    assert!(tree.structurally_eq(&other));
+    assert!(other.structurally_eq(&tree));
+    assert!(tree.structurally_eq(&tree));
+
+    // same number of nodes, different shape
+    let mut different = bits.clone();
+    different.flip_bit(3);
+    different.flip_bit(4);
+    let different = BpTree::<4>::from_bit_vector(different);
+    assert!(!tree.structurally_eq(&different));
+
+    // different number of nodes
+    let mut larger = bits;
+    larger.append_bits(0b01, 2);
+    let larger = BpTree::<4>::from_bit_vector(larger);
+    assert!(!tree.structurally_eq(&larger));
+
+    let empty = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(empty.structurally_eq(&BpTree::<8>::from_bit_vector(BitVec::new())));
+    assert!(!empty.structurally_eq(&tree));
+}
+
+#[test]
+fn test_leaf_lca_depths() {
+    for seed in 9..14 {
+        let tree = random_tree::<32>(seed, 150, 0.5);
+
+        let leaves = tree.leaves().collect::<Vec<_>>();
+        let depths = tree.leaf_lca_depths();
+        assert_eq!(depths.len(), leaves.len() - 1);
+
+        for (pair, &lca_depth) in leaves.windows(2).zip(depths.iter()) {
+            let mut lca = pair[0];
+            while tree.is_ancestor(lca, pair[1]) != Some(true) {
+                lca = tree.parent(lca).unwrap();
+            }
+            assert_eq!(lca_depth, tree.depth(lca), "leaves {pair:?}");
+        }
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 0]));
+    assert!(tree.leaf_lca_depths().is_empty());
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.leaf_lca_depths().is_empty());
+}
+
+#[test]
+fn test_level_sizes() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0,
+    ]));
+    let sizes = tree.level_sizes();
+    assert_eq!(sizes, vec![1, 3, 5]);
+    assert_eq!(sizes[0], 1);
+    assert_eq!(sizes.iter().sum::<usize>(), tree.size());
+    assert_eq!(sizes.len() as u64, tree.height().unwrap() + 1);
+
+    let mut rng = StdRng::from_seed([10; 32]);
+    let mut bit_vec = BitVec::new();
+    for _ in 0..16 {
+        bit_vec.append_word(rng.next_u64());
+    }
+    let tree = BpTree::<32>::from_bit_vector(bit_vec);
+    let mut expected = Vec::new();
+    for node in tree.dfs_iter() {
+        let depth = tree.depth(node) as usize;
+        if depth >= expected.len() {
+            expected.resize(depth + 1, 0);
+        }
+        expected[depth] += 1;
+    }
+    assert_eq!(tree.level_sizes(), expected);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.level_sizes().is_empty());
+}
+
+#[test]
+fn test_mirror() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0,
+    ]));
+    let mirrored = tree.mirror();
+    assert_eq!(mirrored.size(), tree.size());
+    assert!(!mirrored.structurally_eq(&tree));
+    assert!(mirrored.mirror().structurally_eq(&tree));
+
+    let len = tree.vec.len();
+    for node in tree.dfs_iter() {
+        let mirrored_node = len - 1 - tree.close(node).unwrap();
+        assert_eq!(
+            mirrored.children(mirrored_node).count(),
+            tree.children(node).count(),
+            "degree of node {node}"
+        );
+        assert_eq!(mirrored.depth(mirrored_node), tree.depth(node));
+
+        // children appear in reverse order
+        let children = tree
+            .children(node)
+            .map(|c| len - 1 - tree.close(c).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mirrored.rev_children(mirrored_node).collect::<Vec<_>>(),
+            children
+        );
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.mirror().is_empty());
+}
+
+#[test]
+fn test_width() {
+    // star tree with 100 leaves
+    let mut bv = BitVec::new();
+    bv.append_bit(1);
+    for _ in 0..100 {
+        bv.append_bits(0b01, 2);
+    }
+    bv.append_bit(0);
+    let tree = BpTree::<64>::from_bit_vector(bv);
+    assert_eq!(tree.width(), 100);
+    assert_eq!(tree.width(), tree.leaves().count());
+
+    // path
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 0]));
+    assert_eq!(tree.width(), 1);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.width(), 0);
+}
+
+#[test]
+fn test_from_adjacency() {
+    //        4
+    //      / | \
+    //     0  5  2
+    //    / \    \
+    //   1   6    3
+    let edges = [(3, 2), (0, 4), (1, 0), (4, 5), (2, 4), (6, 0)];
+    let (tree, nodes) = BpTree::<4>::from_adjacency(7, &edges, 4).unwrap();
+    assert_eq!(tree.size(), 7);
+    assert_eq!(nodes.len(), 7);
+
+    let input = |handle| nodes[tree.node_index(handle)];
+    let handle = |node| tree.node_handle(nodes.iter().position(|&n| n == node).unwrap());
+
+    let root = tree.root().unwrap();
+    assert_eq!(input(root), 4);
+    assert_eq!(
+        tree.children(root).map(input).collect::<Vec<_>>(),
+        vec![0, 5, 2]
+    );
+    assert_eq!(
+        tree.children(handle(0)).map(input).collect::<Vec<_>>(),
+        vec![1, 6]
+    );
+    assert_eq!(
+        tree.children(handle(2)).map(input).collect::<Vec<_>>(),
+        vec![3]
+    );
+    for (child, parent) in [(0, 4), (5, 4), (2, 4), (1, 0), (6, 0), (3, 2)] {
+        assert_eq!(tree.parent(handle(child)), Some(handle(parent)));
+    }
+    assert_eq!(tree.parent(root), None);
+
+    // single node
+    let (tree, nodes) = BpTree::<4>::from_adjacency(1, &[], 0).unwrap();
+    assert_eq!(tree.size(), 1);
+    assert_eq!(nodes, vec![0]);
+}
+
+#[test]
+fn test_from_adjacency_errors() {
+    // cycle
+    assert!(BpTree::<4>::from_adjacency(4, &[(0, 1), (1, 2), (2, 0), (2, 3)], 3).is_err());
+    // self-loop
+    assert!(BpTree::<4>::from_adjacency(2, &[(0, 1), (1, 1)], 0).is_err());
+    // duplicate edge
+    assert!(BpTree::<4>::from_adjacency(2, &[(0, 1), (1, 0)], 0).is_err());
+    // disconnected
+    assert!(BpTree::<4>::from_adjacency(4, &[(0, 1), (2, 3)], 0).is_err());
+    // cycle in a component not containing the root
+    assert!(BpTree::<4>::from_adjacency(5, &[(0, 1), (2, 3), (3, 4), (4, 2)], 0).is_err());
+    // invalid nodes
+    assert!(BpTree::<4>::from_adjacency(2, &[(0, 2)], 0).is_err());
+    assert!(BpTree::<4>::from_adjacency(2, &[(0, 1)], 2).is_err());
+    assert!(BpTree::<4>::from_adjacency(0, &[], 0).is_err());
+}
+
+#[test]
+fn test_subtree_count_below_depth() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    for node in tree.dfs_iter() {
+        assert_eq!(tree.subtree_count_below_depth(node, 0), 1);
+        assert_eq!(
+            tree.subtree_count_below_depth(node, 1000),
+            tree.subtree_size(node).unwrap()
+        );
+
+        // compare against counting the depths of the subtree nodes
+        for max_depth in 0..6 {
+            let expected = tree
+                .subtree_iter(node)
+                .filter(|&n| tree.depth(n) - tree.depth(node) <= max_depth)
+                .count();
+            assert_eq!(
+                tree.subtree_count_below_depth(node, max_depth as usize),
+                expected,
+                "node {node}, max depth {max_depth}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_postorder_rank() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    for (rank, node) in tree.dfs_post_iter().enumerate() {
+        assert_eq!(tree.postorder_rank(node), rank);
+        assert_eq!(tree.postorder_select(rank), Some(node));
+    }
+    for node in tree.dfs_iter() {
+        assert_eq!(tree.postorder_select(tree.postorder_rank(node)), Some(node));
+    }
+
+    let root = tree.root().unwrap();
+    assert_eq!(tree.postorder_rank(root), tree.size() - 1);
+    assert_eq!(tree.postorder_select(tree.size()), None);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.postorder_select(0), None);
+}
+
+#[test]
+fn test_euler_tour() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+    let tour = tree.euler_tour();
+
+    assert_eq!(tour.len(), 2 * tree.size() - 1);
+    assert_eq!(tour.first(), Some(&root));
+    assert_eq!(tour.last(), Some(&root));
+
+    // each node appears once more than it has children
+    for node in tree.dfs_iter() {
+        let occurrences = tour.iter().filter(|&&v| v == node).count();
+        assert_eq!(occurrences, tree.children(node).count() + 1);
+    }
+
+    // consecutive entries are adjacent in the tree
+    for window in tour.windows(2) {
+        assert!(
+            tree.parent(window[0]) == Some(window[1]) || tree.parent(window[1]) == Some(window[0])
+        );
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.euler_tour(), vec![0]);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.euler_tour().is_empty());
+}
+
+#[test]
+fn test_euler_distance() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let tour = tree.euler_tour();
+    let first_occurrence = |node| tour.iter().position(|&v| v == node).unwrap();
+
+    for u in tree.dfs_iter() {
+        for v in tree.dfs_iter() {
+            assert_eq!(
+                tree.euler_distance(u, v),
+                first_occurrence(u).abs_diff(first_occurrence(v)),
+                "distance between {u} and {v}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_is_binary() {
+    // a complete binary tree of height 2
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 1, 0, 0, 0,
+    ]));
+    assert!(tree.is_binary());
+
+    // the second child of the root has three children
+    let tree =
+        BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 0, 0]));
+    assert!(!tree.is_binary());
+
+    // a path is binary
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 0]));
+    assert!(tree.is_binary());
+
+    assert!(BpTree::<4>::from_bit_vector(BitVec::new()).is_binary());
+}
+
+#[test]
+fn test_num_leaves_and_internal() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.num_leaves(), 0);
+    assert_eq!(tree.num_internal(), 0);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.num_leaves(), 1);
+    assert_eq!(tree.num_internal(), 0);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let leaves = tree.dfs_iter().filter(|&node| tree.is_leaf(node)).count();
+    assert_eq!(tree.num_leaves(), leaves);
+    assert_eq!(tree.num_internal(), tree.size() - leaves);
+    assert_eq!(tree.num_leaves(), 5);
+    assert_eq!(tree.num_internal(), 5);
+}
+
+#[test]
+fn test_subtree_preorder_select() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    for node in tree.dfs_iter() {
+        let size = tree.subtree_size(node).unwrap();
+        let subtree = tree
+            .dfs_iter()
+            .filter(|&v| tree.is_ancestor(node, v) == Some(true))
+            .collect::<Vec<_>>();
+        assert_eq!(subtree.len(), size);
+
+        assert_eq!(tree.subtree_preorder_select(node, 0), Some(node));
+        for (k, &v) in subtree.iter().enumerate() {
+            assert_eq!(tree.subtree_preorder_select(node, k), Some(v));
+        }
+        assert_eq!(tree.subtree_preorder_select(node, size), None);
+        assert_eq!(tree.subtree_preorder_select(node, usize::MAX), None);
+    }
+}
+
+#[test]
+fn test_lowest_marked_ancestor() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+
+    // only the root is marked
+    let mut marks = BitVec::from_zeros(tree.size());
+    marks.set(0, 1).unwrap();
+    let marks = RsVec::from_bit_vec(marks);
+    for node in tree.dfs_iter() {
+        assert_eq!(tree.lowest_marked_ancestor(node, &marks), Some(root));
+    }
+
+    // mark every other node and compare against a naive search over all ancestors
+    let mut rng = StdRng::from_seed([17; 32]);
+    let marks = RsVec::from_bit_vec((0..tree.size()).map(|_| u64::from(rng.gen_bool(0.3))).fold(
+        BitVec::new(),
+        |mut bv, bit| {
+            bv.append_bit(bit);
+            bv
+        },
+    ));
+    for node in tree.dfs_iter() {
+        let expected = tree
+            .dfs_iter()
+            .filter(|&v| tree.is_ancestor(v, node) == Some(true))
+            .filter(|&v| marks.get(tree.node_index(v)) == Some(1))
+            .max_by_key(|&v| tree.depth(v));
+        assert_eq!(tree.lowest_marked_ancestor(node, &marks), expected);
+    }
+
+    // no marks at all
+    let marks = RsVec::from_bit_vec(BitVec::new());
+    assert_eq!(tree.lowest_marked_ancestor(root, &marks), None);
+    assert_eq!(tree.lowest_marked_ancestor(13, &marks), None);
+}
+
+#[test]
+fn test_range_min_excess() {
+    let tree = random_tree::<8>(19, 100, 0.55);
+    let len = tree.size() * 2;
+
+    for start in (0..len).step_by(3) {
+        for end in start + 1..=len {
+            let expected = (start..end).map(|i| (i, tree.excess(i))).reduce(|best, c| {
+                if c.1 < best.1 {
+                    c
+                } else {
+                    best
+                }
+            });
+            assert_eq!(
+                tree.range_min_excess(start..end),
+                expected,
+                "range {start}..{end}"
+            );
+        }
+    }
+
+    assert_eq!(tree.range_min_excess(5..5), None);
+    assert_eq!(tree.range_min_excess(0..len + 1), None);
+    assert_eq!(tree.range_min_excess(0..len), Some((len - 1, 0)));
+}
+
+#[test]
+fn test_bfs_order() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let order = tree.bfs_order();
+    assert_eq!(order.len(), tree.size());
+    assert_eq!(order.bfs_rank(tree.root().unwrap()), 0);
+
+    // naive breadth-first traversal
+    let mut queue = std::collections::VecDeque::from([tree.root().unwrap()]);
+    let mut expected = Vec::new();
+    while let Some(node) = queue.pop_front() {
+        expected.push(node);
+        queue.extend(tree.children(node));
+    }
+
+    for (rank, &node) in expected.iter().enumerate() {
+        assert_eq!(order.bfs_rank(node), rank);
+        assert_eq!(order.bfs_select(rank), Some(node));
+    }
+    assert_eq!(order.bfs_select(tree.size()), None);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    let order = tree.bfs_order();
+    assert!(order.is_empty());
+    assert_eq!(order.bfs_select(0), None);
+}
+
+#[test]
+fn test_cartesian_tree() {
+    //          root
+    //        /   |   \
+    //       5    2    1
+    //           / \    \
+    //          7   3    4
+    //              |
+    //              6
+    let values = [5, 2, 7, 3, 6, 1, 4];
+    let expected_parents = [None, None, Some(1), Some(1), Some(3), None, Some(5)];
+
+    let tree = BpTree::<4>::cartesian_tree(&values);
+    assert_eq!(tree.size(), values.len() + 1);
+    let value_node = |i| tree.node_handle(i + 1);
+    for (i, parent) in expected_parents.into_iter().enumerate() {
+        let expected = parent.map_or(tree.root(), |p| Some(value_node(p)));
+        assert_eq!(tree.parent(value_node(i)), expected, "parent of value {i}");
+    }
+
+    // equal values form a chain, and a decreasing sequence hangs off the root
+    let tree = BpTree::<4>::cartesian_tree(&[2, 2, 2]);
+    assert_eq!(tree.depth(tree.node_handle(3)), 3);
+    let tree = BpTree::<4>::cartesian_tree(&[3, 2, 1]);
+    assert_eq!(tree.children(tree.root().unwrap()).count(), 3);
+
+    // compare against a naive nearest-smaller-value search on random values
+    let mut rng = StdRng::from_seed([21; 32]);
+    let values = (0..300)
+        .map(|_| rng.gen_range(-50..50))
+        .collect::<Vec<i64>>();
+    let tree = BpTree::<8>::cartesian_tree(&values);
+    for i in 0..values.len() {
+        let expected = (0..i)
+            .rev()
+            .find(|&j| values[j] <= values[i])
+            .map_or(tree.root(), |j| Some(tree.node_handle(j + 1)));
+        assert_eq!(tree.parent(tree.node_handle(i + 1)), expected);
+    }
+
+    let tree = BpTree::<4>::cartesian_tree(&[]);
+    assert_eq!(tree.size(), 1);
+}
+
+#[test]
+fn test_subtrees_isomorphic() {
+    // the root has three children, the first two have the same shape (a node with two leaves),
+    // and the third one has one more leaf
+    #[rustfmt::skip]
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1,
+        1, 1, 0, 1, 0, 0,
+        1, 1, 0, 1, 0, 0,
+        1, 1, 0, 1, 0, 1, 0, 0,
+        0,
+    ]));
+    let children = tree.children(0).collect::<Vec<_>>();
+    assert!(tree.subtrees_isomorphic(children[0], children[1]));
+    assert!(!tree.subtrees_isomorphic(children[0], children[2]));
+    assert!(!tree.subtrees_isomorphic(children[2], children[1]));
+    assert!(tree.subtrees_isomorphic(children[2], children[2]));
+    assert!(!tree.subtrees_isomorphic(0, children[0]));
+
+    // same size but different shape
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 0, 0,
+    ]));
+    assert_eq!(tree.subtree_size(1), tree.subtree_size(7));
+    assert!(!tree.subtrees_isomorphic(1, 7));
+
+    // all leaves are isomorphic, also across word boundaries in a large random tree
+    let tree = random_tree::<8>(23, 500, 0.5);
+    let leaves = tree.leaves().collect::<Vec<_>>();
+    assert!(tree.subtrees_isomorphic(leaves[0], *leaves.last().unwrap()));
+    for u in tree.dfs_iter().step_by(7) {
+        for v in tree.dfs_iter().step_by(11) {
+            assert_eq!(
+                tree.subtrees_isomorphic(u, v),
+                tree.subtree_bits(u) == tree.subtree_bits(v),
+                "nodes {u} and {v}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_ancestor_mask() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+
+    let mask = tree.ancestor_mask(root);
+    assert_eq!(mask.len(), tree.size());
+    assert_eq!(mask.count_ones(), 0);
+
+    for node in tree.dfs_iter() {
+        let mask = tree.ancestor_mask(node);
+        assert_eq!(mask.len(), tree.size());
+        assert_eq!(mask.count_ones(), tree.depth(node));
+        for v in tree.dfs_iter() {
+            let expected = v != node && tree.is_ancestor(v, node) == Some(true);
+            assert_eq!(
+                mask.get(tree.node_index(v)),
+                Some(u64::from(expected)),
+                "{v} in ancestor mask of {node}"
+            );
+        }
+    }
+
+    // the common ancestors of two nodes are their lowest common ancestor (here node 7) and its
+    // ancestors
+    let mut common = tree.ancestor_mask(8);
+    common.apply_mask_and(&tree.ancestor_mask(13)).unwrap();
+    let mut expected = tree.ancestor_mask(7);
+    expected.set(tree.node_index(7), 1).unwrap();
+    assert_eq!(common, expected);
+}
+
+#[test]
+fn test_diameter() {
+    // a path of 50 nodes
+    let mut path = vec![1; 50];
+    path.extend(vec![0; 50]);
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&path));
+    assert_eq!(tree.diameter(), tree.size() - 1);
+
+    // complete binary tree of height 3, the longest path connects leaves in different halves
+    #[rustfmt::skip]
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&[
+        1,
+        1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 1, 0, 0, 0,
+        1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 1, 0, 0, 0,
+        0,
+    ]));
+    assert_eq!(tree.size(), 15);
+    assert_eq!(tree.diameter(), 6);
+
+    // the longest path does not need to pass through the root
+    #[rustfmt::skip]
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&[
+        1,
+        1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0,
+        1, 0,
+        0,
+    ]));
+    assert_eq!(tree.diameter(), 6);
+
+    // compare against the maximum distance over all node pairs in a random tree
+    let tree = random_tree::<8>(26, 60, 0.5);
+    let lca_depth = |u, v| {
+        tree.dfs_iter()
+            .filter(|&a| {
+                tree.is_ancestor(a, u) == Some(true) && tree.is_ancestor(a, v) == Some(true)
+            })
+            .map(|a| tree.depth(a))
+            .max()
+            .unwrap()
+    };
+    let expected = tree
+        .leaves()
+        .flat_map(|u| tree.dfs_iter().map(move |v| (u, v)))
+        .map(|(u, v)| tree.depth(u) + tree.depth(v) - 2 * lca_depth(u, v))
+        .max()
+        .unwrap();
+    assert_eq!(tree.diameter() as u64, expected);
+
+    assert_eq!(
+        BpTree::<8>::from_bit_vector(BitVec::from_bits(&[1, 0])).diameter(),
+        0
+    );
+    assert_eq!(BpTree::<8>::from_bit_vector(BitVec::new()).diameter(), 0);
+}
+
+#[test]
+fn test_smallest_enclosing_node() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let leaves = tree.leaves().collect::<Vec<_>>();
+    assert_eq!(leaves, vec![2, 5, 8, 10, 13]);
+
+    // adjacent leaves under the same parent
+    assert_eq!(tree.smallest_enclosing_node(2, 3), Some(7));
+    assert_eq!(tree.parent(8), Some(7));
+    assert_eq!(tree.parent(10), Some(7));
+
+    // leaves in different children of the root
+    let tree2 =
+        BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 0]));
+    assert_eq!(tree2.smallest_enclosing_node(1, 2), tree2.root());
+    assert_eq!(tree2.smallest_enclosing_node(0, 1), Some(1));
+
+    // compare against the deepest common ancestor of all leaves in the interval
+    for lo in 0..leaves.len() {
+        for hi in lo..leaves.len() {
+            let expected = tree
+                .dfs_iter()
+                .filter(|&v| {
+                    leaves[lo..=hi]
+                        .iter()
+                        .all(|&leaf| tree.is_ancestor(v, leaf) == Some(true))
+                })
+                .max_by_key(|&v| tree.depth(v));
+            assert_eq!(
+                tree.smallest_enclosing_node(lo, hi),
+                expected,
+                "leaves {lo}..={hi}"
+            );
+        }
+    }
+    // the only child of the root contains all leaves
+    assert_eq!(tree.smallest_enclosing_node(0, 4), Some(1));
+    assert_eq!(tree.smallest_enclosing_node(0, 5), None);
+    assert_eq!(tree.smallest_enclosing_node(3, 2), None);
+}
+
+#[test]
+fn test_excess_blocks() {
+    const BLOCK: usize = 16;
+
+    let mut rng = StdRng::from_seed([3; 32]);
+    // with an incomplete and with a complete last block
+    for len in [BLOCK * 9 + 5, BLOCK * 8] {
+        let mut bv = BitVec::with_capacity(len);
+        for _ in 0..len {
+            bv.append(rng.gen_bool(0.5));
+        }
+
+        // prefix[k] is the excess of the first k bits
+        let mut prefix = vec![0i64; len + 1];
+        for i in 0..len {
+            prefix[i + 1] = prefix[i] + if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+        }
+
+        let tree = BpTree::<BLOCK>::from_bit_vector(bv);
+        let last_block = (len - 1) / BLOCK;
+
+        for start in 0..len {
+            let block = start / BLOCK;
+            let block_end = min((block + 1) * BLOCK, len);
+
+            for relative_excess in -6..=6 {
+                // forward: the first position after the current block whose prefix excess reaches the target
+                let target = prefix[start + 1] + relative_excess;
+                let expected = (block_end..len)
+                    .find(|&i| prefix[i + 1] == target)
+                    .map(|i| (i / BLOCK, target - prefix[i / BLOCK * BLOCK]));
+                assert_eq!(
+                    tree.forward_excess_block(start, target - prefix[block_end]),
+                    expected,
+                    "forward from {start} with {relative_excess}"
+                );
+                if block == last_block {
+                    assert_eq!(tree.forward_excess_block(start, relative_excess), None);
+                }
+
+                // backward: the last position before the current block whose prefix excess reaches the target
+                let target = prefix[start] + relative_excess;
+                let expected = (0..block * BLOCK)
+                    .rev()
+                    .find(|&j| prefix[j] == target)
+                    .map(|j| (j / BLOCK, target - prefix[(j / BLOCK + 1) * BLOCK]));
+                assert_eq!(
+                    tree.backward_excess_block(start, target - prefix[block * BLOCK]),
+                    expected,
+                    "backward from {start} with {relative_excess}"
+                );
+            }
+        }
+
+        // excess values that can never be reached
+        assert_eq!(tree.forward_excess_block(0, len as i64 + 1), None);
+        assert_eq!(tree.backward_excess_block(len - 1, -(len as i64) - 1), None);
+
+        // out of bounds and empty trees
+        assert_eq!(tree.forward_excess_block(len, 0), None);
+        assert_eq!(tree.backward_excess_block(len, 0), None);
+        let empty = BpTree::<BLOCK>::from_bit_vector(BitVec::new());
+        assert_eq!(empty.forward_excess_block(0, 0), None);
+        assert_eq!(empty.backward_excess_block(0, 0), None);
+    }
+}
+
+#[test]
+fn test_flip_bit() {
+    let mut rng = StdRng::from_seed([27; 32]);
+    let mut bv = BitVec::with_capacity(200);
+    for _ in 0..200 {
+        bv.append(rng.gen_bool(0.5));
+    }
+    let mut tree = BpTree::<16>::from_bit_vector(bv.clone());
+
+    for _ in 0..100 {
+        let index = rng.gen_range(0..200);
+        bv.flip_bit(index);
+        tree.flip_bit(index);
+
+        let expected = BpTree::<16>::from_bit_vector(bv.clone());
+        assert_eq!(tree.vec.rank1(200), expected.vec.rank1(200));
+        for i in 0..200 {
+            assert_eq!(tree.vec.get(i), expected.vec.get(i));
+            for relative_excess in [-2, -1, 0, 1] {
+                assert_eq!(
+                    tree.fwd_search(i, relative_excess),
+                    expected.fwd_search(i, relative_excess)
+                );
+                assert_eq!(
+                    tree.bwd_search(i, relative_excess),
+                    expected.bwd_search(i, relative_excess)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Index out of bounds")]
+fn test_flip_bit_out_of_bounds() {
+    let mut tree = BpTree::<16>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    tree.flip_bit(2);
+}
+
+#[test]
+fn test_write_and_read() {
+    let mut rng = StdRng::from_seed([36; 32]);
+    for len in [0, 2, 64, 100, 1000] {
+        let mut bv = BitVec::with_capacity(len);
+        for _ in 0..len {
+            bv.append(rng.gen_bool(0.5));
+        }
+        let tree = BpTree::<16>::from_bit_vector(bv);
+
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes).unwrap();
+        let restored = BpTree::<16>::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(restored.vec, tree.vec);
+        assert_eq!(restored.min_max_tree, tree.min_max_tree);
+        for i in 0..len {
+            assert_eq!(restored.fwd_search(i, -1), tree.fwd_search(i, -1));
+            assert_eq!(restored.bwd_search(i, -1), tree.bwd_search(i, -1));
+        }
+
+        // truncated input and mismatching block sizes are rejected
+        assert!(BpTree::<16>::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+        assert_eq!(
+            BpTree::<32>::read_from(&mut bytes.as_slice())
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    // a min-max tree that doesn't match the length of the expression
+    let mut bytes = Vec::new();
+    BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 0, 1, 0]))
+        .write_to(&mut bytes)
+        .unwrap();
+    bytes[8..16].copy_from_slice(&4u64.to_le_bytes());
+    assert_eq!(
+        BpTree::<4>::read_from(&mut bytes.as_slice())
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_ancestor_of_leaf_at_depth() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+
+    for leaf in tree.leaves() {
+        let depth = tree.depth(leaf);
+        assert_eq!(tree.ancestor_of_leaf_at_depth(leaf, 0), Some(root));
+        assert_eq!(tree.ancestor_of_leaf_at_depth(leaf, depth), Some(leaf));
+        assert_eq!(tree.ancestor_of_leaf_at_depth(leaf, depth + 1), None);
+
+        for d in 0..=depth {
+            let ancestor = tree.ancestor_of_leaf_at_depth(leaf, d).unwrap();
+            assert_eq!(tree.depth(ancestor), d);
+            assert_eq!(tree.is_ancestor(ancestor, leaf), Some(true));
+        }
+    }
+}
+
+#[test]
+fn test_search_tiny_trees() {
+    // searching in an empty tree must not panic
+    let tree = BpTree::<8>::from_bit_vector(BitVec::new());
+    for index in 0..3 {
+        for excess in -2..=2 {
+            assert_eq!(tree.fwd_search(index, excess), None);
+            assert_eq!(tree.bwd_search(index, excess), None);
+        }
+    }
+    assert_eq!(
+        tree.fwd_search_multi_cached(&[(0, -1), (1, 1)], &mut BlockExcessCache::default()),
+        vec![None, None]
+    );
+
+    // single-block trees: results within the block are found, all others fail
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 0, 0]));
+    assert_eq!(tree.fwd_search(0, -1), Some(5));
+    assert_eq!(tree.fwd_search(0, -2), None);
+    assert_eq!(tree.fwd_search(5, -1), None);
+    assert_eq!(tree.bwd_search(5, 5), None);
+    assert_eq!(tree.bwd_search(5, -5), None);
+    assert_eq!(tree.bwd_search(0, -1), None);
+    assert_eq!(tree.close(0), Some(5));
+    assert_eq!(tree.open(5), Some(0));
+
+    // a tree filling exactly one block
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0]));
+    for index in 0..8 {
+        for excess in [-8, -5, 5, 8] {
+            assert_eq!(tree.fwd_search(index, excess), None);
+            assert_eq!(tree.bwd_search(index, excess), None);
+        }
+    }
+    assert_eq!(tree.close(0), Some(7));
+
+    // a single node tree
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.fwd_search(0, -1), Some(1));
+    assert_eq!(tree.fwd_search(0, -2), None);
+    assert_eq!(tree.bwd_search(1, 3), None);
+    assert_eq!(tree.open(1), Some(0));
+}
+
+#[test]
+fn test_root_to_leaf_paths() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+    let paths = tree.root_to_leaf_paths();
+
+    assert_eq!(paths.len(), tree.leaves().count());
+    for (path, leaf) in paths.iter().zip(tree.leaves()) {
+        assert_eq!(path.first(), Some(&root));
+        assert_eq!(path.last(), Some(&leaf));
+        assert!(tree.is_leaf(leaf));
+        assert_eq!(path.len() as u64, tree.depth(leaf) + 1);
+        for edge in path.windows(2) {
+            assert_eq!(tree.parent(edge[1]), Some(edge[0]));
+        }
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.root_to_leaf_paths(), vec![vec![0]]);
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.root_to_leaf_paths().is_empty());
+}
+
+#[test]
+fn test_preorder_next_prev() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+
+    let mut stepped = Vec::new();
+    let mut current = tree.root();
+    while let Some(node) = current {
+        stepped.push(node);
+        current = tree.preorder_next(node);
+    }
+    assert_eq!(stepped, tree.dfs_iter().collect::<Vec<_>>());
+
+    let mut stepped = Vec::new();
+    let mut current = tree.dfs_iter().last();
+    while let Some(node) = current {
+        stepped.push(node);
+        current = tree.preorder_prev(node);
+    }
+    stepped.reverse();
+    assert_eq!(stepped, tree.dfs_iter().collect::<Vec<_>>());
+
+    // the definition in terms of tree navigation
+    for node in tree.dfs_iter() {
+        let mut expected = tree.first_child(node);
+        let mut ancestor = Some(node);
+        while expected.is_none() {
+            let Some(a) = ancestor else { break };
+            expected = tree.next_sibling(a);
+            ancestor = tree.parent(a);
+        }
+        assert_eq!(tree.preorder_next(node), expected, "next of {node}");
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.preorder_next(0), None);
+    assert_eq!(tree.preorder_prev(0), None);
+}
+
+#[test]
+fn test_common_prefix_length() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&EXAMPLE_TREE));
+    let root = tree.root().unwrap();
+
+    for u in tree.dfs_iter() {
+        assert_eq!(tree.common_prefix_length(u, u) as u64, tree.depth(u) + 1);
+        assert_eq!(tree.common_prefix_length(u, root), 1);
+
+        // compare against the intersection of the root paths
+        let path = |mut node| {
+            let mut path = vec![node];
+            while let Some(parent) = tree.parent(node) {
+                path.push(parent);
+                node = parent;
+            }
+            path
+        };
+        let u_path = path(u);
+        for v in tree.dfs_iter() {
+            let shared = path(v).iter().filter(|n| u_path.contains(n)).count();
+            assert_eq!(tree.common_prefix_length(u, v), shared, "{u} and {v}");
+            assert_eq!(tree.common_prefix_length(v, u), shared, "{v} and {u}");
+        }
+    }
+
+    // children and grandchildren in different subtrees of the root
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 1, 0, 0, 0]));
+    let root = tree.root().unwrap();
+    let children = tree.children(root).collect::<Vec<_>>();
+    assert_eq!(children.len(), 2);
+    assert_eq!(tree.common_prefix_length(children[0], children[1]), 1);
+    assert_eq!(tree.common_prefix_length(2, 6), 1);
+    assert_eq!(tree.common_prefix_length(1, 2), 2);
+}
+
+#[test]
+fn test_subtree_bits() {
+    let tree = random_tree::<64>(13, 250, 0.55);
+
+    for node in tree.dfs_iter() {
+        let bits = tree.subtree_bits(node);
+        assert_eq!(bits.len(), 2 * tree.subtree_size(node).unwrap());
+
+        // the bits are balanced and never drop below zero excess before the end
+        let mut excess = 0i64;
+        for (i, bit) in bits.iter().enumerate() {
+            excess += if bit == 1 { 1 } else { -1 };
+            assert!(excess > 0 || i == bits.len() - 1);
+        }
+        assert_eq!(excess, 0);
+
+        let subtree = BpTree::<64>::from_bit_vector(bits);
+        assert_eq!(subtree.size(), tree.subtree_size(node).unwrap());
+    }
+}
+
+#[test]
+fn test_heaviest_child() {
+    // the root has three children with 1, 3, and 3 nodes in their subtrees
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 0, 0,
+    ]));
+    let root = tree.root().unwrap();
+    assert_eq!(tree.children(root).collect::<Vec<_>>(), vec![1, 3, 9]);
+
+    // ties go to the first child
+    assert_eq!(tree.heaviest_child(root), Some(3));
+    assert_eq!(tree.heaviest_child(3), Some(4));
+    assert_eq!(tree.heaviest_child(9), Some(10));
+
+    for leaf in tree.leaves() {
+        assert_eq!(tree.heaviest_child(leaf), None);
+    }
+
+    // a heavy child that is not the first child
+    let tree =
+        BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 1, 0, 1, 0, 1, 1, 1, 0, 0, 0, 0]));
+    assert_eq!(tree.heaviest_child(0), Some(5));
+    assert_eq!(tree.heaviest_child(5), Some(6));
+}
+
+#[test]
+fn test_heavy_path_decomposition() {
+    let tree = random_tree::<64>(15, 1000, 0.52);
+    let chains = tree.heavy_path_decomposition();
+    assert_eq!(chains.len(), tree.size());
+    assert_eq!(chains[0], 0);
+
+    // each node continues the chain of its parent exactly if it is the heavy child
+    for node in tree.dfs_iter() {
+        let index = tree.node_index(node);
+        if let Some(parent) = tree.parent(node) {
+            let same_chain = chains[index] == chains[tree.node_index(parent)];
+            assert_eq!(same_chain, tree.heaviest_child(parent) == Some(node));
+        }
+    }
+
+    // each chain is a contiguous downward path
+    let num_chains = chains.iter().max().unwrap() + 1;
+    for chain in 0..num_chains {
+        let mut nodes = tree
+            .dfs_iter()
+            .filter(|&node| chains[tree.node_index(node)] == chain)
+            .collect::<Vec<_>>();
+        assert!(!nodes.is_empty(), "chain {chain} is empty");
+        nodes.sort_by_key(|&node| tree.depth(node));
+        for edge in nodes.windows(2) {
+            assert_eq!(tree.parent(edge[1]), Some(edge[0]));
+        }
+    }
+
+    // root-to-leaf paths have logarithmically many light edges
+    let max_light_edges = tree.size().ilog2() as usize;
+    for path in tree.root_to_leaf_paths() {
+        let light_edges = path
+            .windows(2)
+            .filter(|edge| chains[tree.node_index(edge[0])] != chains[tree.node_index(edge[1])])
+            .count();
+        assert!(light_edges <= max_light_edges);
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.heavy_path_decomposition().is_empty());
+}
+
+#[test]
+fn test_depth_profile() {
+    let tree = random_tree::<64>(17, 500, 0.6);
+
+    let (profile, bits) = tree.depth_profile();
+    assert!(1 << bits > tree.height().unwrap());
+    assert_eq!(profile.len(), tree.size() * bits);
+    for node in tree.dfs_iter() {
+        assert_eq!(
+            profile.unpack_element(tree.node_index(node), bits),
+            Some(tree.depth(node))
+        );
+    }
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    let (profile, bits) = tree.depth_profile();
+    assert_eq!(bits, 1);
+    assert_eq!(profile.unpack_element(0, bits), Some(0));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert!(tree.depth_profile().0.is_empty());
+}
+
+#[test]
+fn test_deepest_descent() {
+    // the deepest node at depth 4 is in the middle of the tree, after a shallower branch
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 0, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0,
+    ]));
+    assert_eq!(tree.deepest_descent(), Some((8, 4)));
+    assert_eq!(tree.depth(8), 4);
+    assert_eq!(Some(tree.depth(8)), tree.height());
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 0, 1, 1, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0,
+    ]));
+    assert_eq!(tree.deepest_descent(), Some((11, 5)));
+
+    // compare against the first node of maximum depth on a larger tree
+    let tree = random_tree::<64>(18, 1500, 0.5);
+    let height = tree.height().unwrap();
+    let deepest = tree.dfs_iter().find(|&node| tree.depth(node) == height);
+    assert_eq!(tree.deepest_descent(), deepest.map(|node| (node, height)));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.deepest_descent(), Some((0, 0)));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.deepest_descent(), None);
+}
+
+#[test]
+fn test_centroid() {
+    fn assert_centroid<const B: usize>(tree: &BpTree<B>) {
+        let n = tree.size();
+        let centroid = tree.centroid().unwrap();
+        assert!(n - tree.subtree_size(centroid).unwrap() <= n / 2);
+        for child in tree.children(centroid) {
+            assert!(tree.subtree_size(child).unwrap() <= n / 2);
+        }
+    }
+
+    // path, star, caterpillar, and a random tree
+    let mut path = vec![1; 50];
+    path.extend(vec![0; 50]);
+    assert_centroid(&BpTree::<8>::from_bit_vector(BitVec::from_bits(&path)));
+
+    let mut star = vec![1];
+    for _ in 0..30 {
+        star.extend([1, 0]);
+    }
+    star.push(0);
+    let tree = BpTree::<8>::from_bit_vector(BitVec::from_bits(&star));
+    assert_eq!(tree.centroid(), Some(0));
+    assert_centroid(&tree);
+
+    let mut caterpillar = Vec::new();
+    for _ in 0..20 {
+        caterpillar.extend([1, 1, 0]);
+    }
+    caterpillar.extend(vec![0; 20]);
+    assert_centroid(&BpTree::<8>::from_bit_vector(BitVec::from_bits(
+        &caterpillar,
+    )));
+
+    // a random tree below a single root
+    assert_centroid(&random_tree::<64>(20, 1000, 0.5));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[1, 0]));
+    assert_eq!(tree.centroid(), Some(0));
+
+    let tree = BpTree::<4>::from_bit_vector(BitVec::new());
+    assert_eq!(tree.centroid(), None);
+}
+
+#[test]
+fn test_child_ranges() {
+    let tree = BpTree::<4>::from_bit_vector(BitVec::from_bits(&[
+        1, 1, 1, 0, 1, 1, 0, 1, 1, 0, 1, 0, 1, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 0, 0, 0,
+    ]));
+
+    for node in tree.dfs_iter() {
+        let ranges = tree.child_ranges(node);
+        assert_eq!(ranges.len(), tree.children(node).count());
+
+        // the ranges are contiguous and cover the interior of the node
+        let close = tree.close(node).unwrap();
+        let mut expected_start = node + 1;
+        for (range, child) in ranges.iter().zip(tree.children(node)) {
+            assert_eq!(range.start, expected_start);
+            assert_eq!(range.start, child);
+            assert_eq!(range.len(), 2 * tree.subtree_size(child).unwrap());
+            expected_start = range.end;
+        }
+        assert_eq!(expected_start, close);
+    }
+}
+
+#[test]
+fn test_narrowest_excess_type() {
+    let mut bv = BitVec::from_bits(&[1; 1000]);
+    bv.extend_bitvec(&BitVec::from_bits(&[0; 1000]));
+    let tree = BpTree::<64>::from_bit_vector(bv.clone());
+    assert!(matches!(tree.min_max_tree, AdaptiveMinMaxTree::I16(_)));
+
+    // the narrow tree answers the same queries as a wide one
+    let wide = MinMaxTree64::excess_tree(&bv, 64);
+    assert!(tree.min_max_tree.heap_size() < wide.heap_size());
+    for block in 0..wide.num_blocks() {
+        for excess in -3..=3 {
+            assert_eq!(
+                tree.min_max_tree.fwd_search(block, excess),
+                wide.fwd_search(block, excess)
+            );
+            assert_eq!(
+                tree.min_max_tree.bwd_search(block, excess),
+                wide.bwd_search(block, excess)
+            );
+        }
+    }
+}
+
+/// The serde layout of a [`BpTree`] in vers-vecs 1.6, before the excess type was narrowed
+#[cfg(feature = "serde")]
+mod layout_1_6 {
+    use crate::RsVec;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename = "BpTree")]
+    pub(super) struct BpTree {
+        pub(super) vec: RsVec,
+        pub(super) min_max_tree: MinMaxTree,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename = "MinMaxTree")]
+    pub(super) struct MinMaxTree {
+        pub(super) nodes: Vec<ExcessNode>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename = "ExcessNode")]
+    pub(super) struct ExcessNode {
+        pub(super) total: i64,
+        pub(super) min: i64,
+        pub(super) max: i64,
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    fn to_bytes<T: serde::Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    // the larger tree needs more than 16 bits of excess
+    for (seed, nodes) in [(43, 1), (44, 300), (45, 20000)] {
+        let tree = random_tree::<64>(seed, nodes, 0.5);
+        let bytes = to_bytes(&tree);
+
+        let restored: BpTree<64> = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(restored.vec, tree.vec);
+        assert_eq!(restored.min_max_tree, tree.min_max_tree);
+
+        // the layout of 1.6 is kept in both directions
+        let old: layout_1_6::BpTree = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(old.min_max_tree.nodes.len(), tree.min_max_tree.num_nodes());
+        let root = &old.min_max_tree.nodes[0];
+        assert_eq!((root.total, root.min), (0, 0));
+        assert_eq!(to_bytes(&old), bytes);
+    }
+
+    // values that don't fit into the excess type are rejected
+    let tree = random_tree::<64>(46, 10, 0.5);
+    let mut old: layout_1_6::BpTree = ciborium::from_reader(to_bytes(&tree).as_slice()).unwrap();
+    old.min_max_tree.nodes[0].max = 1 << 20;
+    assert!(ciborium::from_reader::<BpTree<64>, _>(to_bytes(&old).as_slice()).is_err());
+}
@@ -13,119 +13,553 @@
 
 use crate::BitVec;
 use std::cmp::max;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{self, Read, Write};
 use std::num::NonZeroUsize;
 
+/// An integer type that stores the excess values in the nodes of a [`MinMaxTree`].
+/// Narrower types reduce the size of the tree, but limit the length of the parenthesis
+/// expression, because the excess of a node can be as large as the number of bits it covers.
+///
+/// The trait is private to the crate and thus sealed. It is implemented for `i16`, `i32`, and
+/// `i64`. All computations are performed on `i64`, only the stored values are narrowed.
+pub(crate) trait ExcessInt: Copy + Debug + Default + Eq + Into<i64> + TryFrom<i64> {
+    /// The maximum length of a parenthesis expression whose excess values fit into the type.
+    const MAX_LEN: usize;
+
+    /// Convert an excess value into the type.
+    ///
+    /// # Panics
+    /// Panics if the value doesn't fit, which cannot happen for trees over expressions of at most
+    /// [`MAX_LEN`] bits.
+    ///
+    /// [`MAX_LEN`]: ExcessInt::MAX_LEN
+    fn from_excess(excess: i64) -> Self {
+        Self::try_from(excess)
+            .unwrap_or_else(|_| panic!("excess {excess} does not fit into the excess type"))
+    }
+}
+
+impl ExcessInt for i16 {
+    const MAX_LEN: usize = i16::MAX as usize;
+}
+
+impl ExcessInt for i32 {
+    const MAX_LEN: usize = i32::MAX as usize;
+}
+
+impl ExcessInt for i64 {
+    const MAX_LEN: usize = usize::MAX;
+}
+
 /// A singular node in a binary min-max tree that is part of the [`BpTree`] data structure.
 ///
 /// [`BpTree`]: crate::trees::bp::BpTree
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-struct ExcessNode {
+pub(crate) struct ExcessNode<E: ExcessInt> {
     /// excess from l..=r in the node [l, r]
-    total: i64,
+    total: E,
 
     /// minimum (relative) excess in the node [l, r]
-    min: i64,
+    min: E,
 
     /// maximum (relative) excess in the node [l, r]
-    max: i64,
+    max: E,
+}
+
+impl<E: ExcessInt> ExcessNode<E> {
+    fn new(total: i64, min: i64, max: i64) -> Self {
+        Self {
+            total: E::from_excess(total),
+            min: E::from_excess(min),
+            max: E::from_excess(max),
+        }
+    }
+
+    /// Create a node from excess values, or return `None` if they don't fit into the excess type.
+    fn try_new(total: i64, min: i64, max: i64) -> Option<Self> {
+        Some(Self {
+            total: E::try_from(total).ok()?,
+            min: E::try_from(min).ok()?,
+            max: E::try_from(max).ok()?,
+        })
+    }
+
+    fn total(&self) -> i64 {
+        self.total.into()
+    }
+
+    fn min(&self) -> i64 {
+        self.min.into()
+    }
+
+    fn max(&self) -> i64 {
+        self.max.into()
+    }
+}
+
+/// The heap memory used by a min-max tree, split into the internal nodes and the leaves.
+/// Each leaf covers one block of the parenthesis expression, so comparing both parts shows the
+/// overhead of the tree structure for a given block size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SizeBreakdown {
+    /// The number of bytes used by the internal nodes, including nodes without children
+    pub(crate) internal_nodes_bytes: usize,
+
+    /// The number of bytes used by the leaves
+    pub(crate) leaf_nodes_bytes: usize,
+
+    /// The total number of nodes in the tree
+    #[allow(dead_code)] // only read when profiling
+    pub(crate) node_count: usize,
+}
+
+/// The reason a bit vector is not a balanced parenthesis expression, as returned by
+/// [`BpTree::try_from_bit_vector`].
+///
+/// [`BpTree::try_from_bit_vector`]: crate::trees::bp::BpTree::try_from_bit_vector
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExcessError {
+    /// The closing parenthesis at `position` has no matching opening parenthesis, i.e. the excess
+    /// drops below zero. This is the first such position.
+    UnmatchedClose {
+        /// The position of the closing parenthesis
+        position: usize,
+    },
+
+    /// The opening parenthesis at `position` and possibly others are never closed, i.e. the total
+    /// excess of the expression is positive. This is the first unclosed parenthesis.
+    UnclosedOpen {
+        /// The position of the first opening parenthesis that is never closed
+        position: usize,
+
+        /// The total excess of the expression, i.e. the number of unclosed parentheses
+        excess: i64,
+    },
+}
+
+impl Display for ExcessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExcessError::UnmatchedClose { position } => write!(
+                f,
+                "closing parenthesis at position {position} has no matching opening parenthesis"
+            ),
+            ExcessError::UnclosedOpen { position, excess } => write!(
+                f,
+                "opening parenthesis at position {position} is never closed ({excess} unclosed parentheses in total)"
+            ),
+        }
+    }
 }
 
+impl Error for ExcessError {}
+
 /// A binary min-max tree that is part of the [`BpTree`] data structure.
+/// The excess values are stored in the integer type `E`, which limits the length of the
+/// parenthesis expression to [`ExcessInt::MAX_LEN`] bits.
 ///
 /// [`BpTree`]: crate::trees::bp::BpTree
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub(crate) struct MinMaxTree {
-    nodes: Box<[ExcessNode]>,
+pub(crate) struct MinMaxTree<E: ExcessInt = i64> {
+    nodes: Box<[ExcessNode<E>]>,
 }
 
-impl MinMaxTree {
+/// A min-max tree storing its excess values in `i64`, which supports expressions of any length.
+/// [`BpTree`] uses it for expressions too long for the narrower types, see [`AdaptiveMinMaxTree`].
+///
+/// [`BpTree`]: crate::trees::bp::BpTree
+pub(crate) type MinMaxTree64 = MinMaxTree<i64>;
+
+impl<E: ExcessInt> MinMaxTree<E> {
+    /// Build the min-max tree over the parenthesis expression `bit_vec`, where each leaf covers
+    /// `block_size` bits, except for the last leaf, which may cover fewer.
+    /// The tree has exactly `bit_vec.len().div_ceil(block_size)` leaves, so a `block_size` equal
+    /// to or larger than the length of the vector results in a single leaf.
+    /// An empty vector results in an empty tree.
+    ///
+    /// # Panics
+    /// Panics if `block_size` is zero, or if the vector is longer than [`ExcessInt::MAX_LEN`] of
+    /// the excess type.
     pub(crate) fn excess_tree(bit_vec: &BitVec, block_size: usize) -> Self {
+        Self::build_excess_tree::<false>(bit_vec, block_size)
+            .unwrap_or_else(|_| unreachable!("unchecked construction cannot fail"))
+    }
+
+    /// Build one min-max tree over the parenthesis expression `bit_vec` for each of the given
+    /// block sizes, as [`excess_tree`] would for each of them.
+    /// The bits are scanned once, and the running excess is split into blocks for all block sizes
+    /// simultaneously, which saves passes over large vectors when comparing block sizes.
+    /// The trees are returned in the order of `block_sizes`.
+    ///
+    /// # Panics
+    /// Panics if a block size is zero, or if the vector is longer than [`ExcessInt::MAX_LEN`] of
+    /// the excess type.
+    ///
+    /// [`excess_tree`]: MinMaxTree::excess_tree
+    #[allow(dead_code)] // tuning aid for comparing block sizes, only called from tests
+    pub(crate) fn build_many(bit_vec: &BitVec, block_sizes: &[usize]) -> Vec<Self> {
+        assert!(
+            block_sizes.iter().all(|&block_size| block_size > 0),
+            "block size must be positive"
+        );
+        assert!(
+            bit_vec.len() <= E::MAX_LEN,
+            "bit vector is too long for the excess type"
+        );
+
         if bit_vec.is_empty() {
-            return Self::default();
+            return block_sizes.iter().map(|_| Self::default()).collect();
+        }
+
+        // per block size: the statistics of the finished blocks, and the absolute excess at the
+        // start of the current block together with the absolute minimum and maximum in it
+        let mut block_stats = block_sizes
+            .iter()
+            .map(|&block_size| Vec::with_capacity(bit_vec.len().div_ceil(block_size)))
+            .collect::<Vec<_>>();
+        let mut current = vec![(0, i64::MAX, i64::MIN); block_sizes.len()];
+
+        // bottom up construction
+        let mut excess = 0;
+        for i in 0..bit_vec.len() {
+            let step = if bit_vec.is_bit_set_unchecked(i) {
+                1
+            } else {
+                -1
+            };
+
+            for ((&block_size, stats), (start, min, max)) in
+                block_sizes.iter().zip(&mut block_stats).zip(&mut current)
+            {
+                if i > 0 && i % block_size == 0 {
+                    stats.push((excess - *start, *min - *start, *max - *start));
+                    (*start, *min, *max) = (excess, i64::MAX, i64::MIN);
+                }
+                *min = (*min).min(excess + step);
+                *max = (*max).max(excess + step);
+            }
+            excess += step;
         }
 
-        let num_leaves = bit_vec.len().div_ceil(block_size);
-        let num_internal_nodes = max(1, (1 << (num_leaves as f64).log2().ceil() as usize) - 1);
+        block_stats
+            .iter_mut()
+            .zip(&current)
+            .map(|(stats, &(start, min, max))| {
+                stats.push((excess - start, min - start, max - start));
+                Self::rebuild_from_blocks(stats)
+            })
+            .collect()
+    }
+
+    /// Build the min-max tree like [`excess_tree`], but validate that `bit_vec` is a balanced
+    /// parenthesis expression in the same pass.
+    ///
+    /// # Errors
+    /// Returns an [`ExcessError`] describing the first problem found: a closing parenthesis
+    /// without a matching opening parenthesis, or opening parentheses that are never closed.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`excess_tree`].
+    ///
+    /// [`excess_tree`]: MinMaxTree::excess_tree
+    pub(crate) fn try_excess_tree(
+        bit_vec: &BitVec,
+        block_size: usize,
+    ) -> Result<Self, ExcessError> {
+        Self::build_excess_tree::<true>(bit_vec, block_size)
+    }
+
+    /// Build the min-max tree in a single pass over `bit_vec`.
+    /// If `CHECKED` is set, the absolute excess is tracked as well to validate the expression,
+    /// otherwise the construction cannot fail.
+    fn build_excess_tree<const CHECKED: bool>(
+        bit_vec: &BitVec,
+        block_size: usize,
+    ) -> Result<Self, ExcessError> {
+        assert!(block_size > 0, "block size must be positive");
+        assert!(
+            bit_vec.len() <= E::MAX_LEN,
+            "bit vector is too long for the excess type"
+        );
+
+        if bit_vec.is_empty() {
+            return Ok(Self::default());
+        }
 
-        let mut nodes = vec![ExcessNode::default(); num_leaves + num_internal_nodes];
+        let mut block_stats = Vec::with_capacity(bit_vec.len().div_ceil(block_size));
         let mut total_excess = 0;
         let mut min_excess = i64::MAX;
         let mut max_excess = i64::MIN;
 
+        // absolute excess and the position after which it was zero the last time,
+        // i.e. the first opening parenthesis that is never closed
+        let mut excess = 0;
+        let mut first_unclosed = 0;
+
         // bottom up construction
         for i in 0..bit_vec.len() {
             if i > 0 && i % block_size == 0 {
-                nodes[num_internal_nodes + i / block_size - 1] = ExcessNode {
-                    total: total_excess,
-                    min: min_excess,
-                    max: max_excess,
-                };
+                block_stats.push((total_excess, min_excess, max_excess));
                 total_excess = 0;
                 min_excess = i64::MAX;
                 max_excess = i64::MIN;
             }
-            total_excess += if bit_vec.is_bit_set_unchecked(i) {
+            let step = if bit_vec.is_bit_set_unchecked(i) {
                 1
             } else {
                 -1
             };
+            total_excess += step;
             min_excess = min_excess.min(total_excess);
             max_excess = max_excess.max(total_excess);
+
+            if CHECKED {
+                excess += step;
+                if excess < 0 {
+                    return Err(ExcessError::UnmatchedClose { position: i });
+                }
+                if excess == 0 {
+                    first_unclosed = i + 1;
+                }
+            }
+        }
+        block_stats.push((total_excess, min_excess, max_excess));
+
+        if CHECKED && excess != 0 {
+            return Err(ExcessError::UnclosedOpen {
+                position: first_unclosed,
+                excess,
+            });
         }
-        nodes[num_internal_nodes + num_leaves - 1] = ExcessNode {
-            total: total_excess,
-            min: min_excess,
-            max: max_excess,
+
+        Ok(Self::rebuild_from_blocks(&block_stats))
+    }
+
+    /// Build the min-max tree like [`excess_tree`], but scan the blocks of `bit_vec` in parallel
+    /// using rayon's global thread pool.
+    /// Only the leaves are computed in parallel, the internal nodes are filled sequentially
+    /// afterwards, which is cheap compared to the scan. The result is identical to [`excess_tree`].
+    ///
+    /// # Panics
+    /// Panics under the same conditions as [`excess_tree`].
+    ///
+    /// [`excess_tree`]: MinMaxTree::excess_tree
+    #[cfg(feature = "rayon")]
+    pub(crate) fn excess_tree_parallel(bit_vec: &BitVec, block_size: usize) -> Self {
+        use rayon::prelude::*;
+
+        assert!(block_size > 0, "block size must be positive");
+        assert!(
+            bit_vec.len() <= E::MAX_LEN,
+            "bit vector is too long for the excess type"
+        );
+
+        let block_stats = (0..bit_vec.len().div_ceil(block_size))
+            .into_par_iter()
+            .map(|block| {
+                let start = block * block_size;
+                let end = bit_vec.len().min(start + block_size);
+
+                let mut total_excess = 0;
+                let mut min_excess = i64::MAX;
+                let mut max_excess = i64::MIN;
+                for i in start..end {
+                    total_excess += if bit_vec.is_bit_set_unchecked(i) {
+                        1
+                    } else {
+                        -1
+                    };
+                    min_excess = min_excess.min(total_excess);
+                    max_excess = max_excess.max(total_excess);
+                }
+                (total_excess, min_excess, max_excess)
+            })
+            .collect::<Vec<_>>();
+
+        Self::rebuild_from_blocks(&block_stats)
+    }
+
+    /// Build the min-max tree from precomputed `(total, min, max)` excess statistics of each block,
+    /// where `min` and `max` are relative to the start of the block.
+    /// This way, only changed blocks need to be recomputed after modifying the parenthesis
+    /// expression, and the tree can be assembled from the block array.
+    /// An empty array results in an empty tree.
+    pub(crate) fn rebuild_from_blocks(block_stats: &[(i64, i64, i64)]) -> Self {
+        if block_stats.is_empty() {
+            return Self::default();
+        }
+
+        let num_leaves = block_stats.len();
+        let num_internal_nodes = max(1, num_leaves.next_power_of_two() - 1);
+
+        let mut tree = Self {
+            nodes: vec![ExcessNode::default(); num_internal_nodes + num_leaves].into_boxed_slice(),
         };
+        tree.update_leaves(0, block_stats);
+        tree
+    }
 
-        let mut current_level_size = max(1, num_leaves.next_power_of_two() / 2);
-        let mut current_level_start = num_internal_nodes - current_level_size;
-        loop {
-            for i in 0..current_level_size {
-                let left_child_index = (current_level_start + i) * 2 + 1;
-                let right_child_index = (current_level_start + i) * 2 + 2;
-
-                if left_child_index < nodes.len() {
-                    if right_child_index < nodes.len() {
-                        let left_child = &nodes[left_child_index];
-                        let right_child = &nodes[right_child_index];
-                        nodes[current_level_start + i] = ExcessNode {
-                            total: left_child.total + right_child.total,
-                            min: left_child.min.min(left_child.total + right_child.min),
-                            max: left_child.max.max(left_child.total + right_child.max),
-                        };
+    /// Overwrite the `(total, min, max)` excess statistics of the leaves starting at leaf index
+    /// `start_leaf` (the first leaf is indexed with 0) with `block_stats`, and recompute the
+    /// ancestors of the changed leaves.
+    /// The ancestors are recomputed level by level, so each ancestor shared by several changed
+    /// leaves is recomputed only once, which takes `O(m + log n)` time for `m` changed leaves.
+    ///
+    /// The changed leaves must exist in the tree, otherwise the function may panic.
+    pub(crate) fn update_leaves(&mut self, start_leaf: usize, block_stats: &[(i64, i64, i64)]) {
+        if block_stats.is_empty() {
+            return;
+        }
+
+        let first_leaf = self.first_leaf();
+        debug_assert!(
+            first_leaf + start_leaf + block_stats.len() <= self.nodes.len(),
+            "changed leaves are out of bounds"
+        );
+
+        for (node, &(total, min, max)) in self.nodes[first_leaf + start_leaf..]
+            .iter_mut()
+            .zip(block_stats)
+        {
+            *node = ExcessNode::new(total, min, max);
+        }
+
+        // inclusive range of changed nodes on the current level
+        let mut begin = first_leaf + start_leaf;
+        let mut end = begin + block_stats.len() - 1;
+        while begin > 0 {
+            begin = (begin - 1) / 2;
+            end = (end - 1) / 2;
+
+            for index in begin..=end {
+                let left_child_index = index * 2 + 1;
+                let right_child_index = index * 2 + 2;
+
+                if left_child_index < self.nodes.len() {
+                    if right_child_index < self.nodes.len() {
+                        let left_child = &self.nodes[left_child_index];
+                        let right_child = &self.nodes[right_child_index];
+                        self.nodes[index] = ExcessNode::new(
+                            left_child.total() + right_child.total(),
+                            left_child.min().min(left_child.total() + right_child.min()),
+                            left_child.max().max(left_child.total() + right_child.max()),
+                        );
                     } else {
-                        nodes[current_level_start + i] = nodes[left_child_index].clone();
+                        self.nodes[index] = self.nodes[left_child_index].clone();
                     }
                 }
             }
+        }
+    }
 
-            // if this was the root level, break the loop
-            if current_level_size == 1 {
-                break;
-            }
+    /// Update the tree after the bit at `bit_index` of the parenthesis expression `bit_vec` was
+    /// set to `new_value`, where each leaf covers `block_size` bits as in [`excess_tree`].
+    /// Only the block containing the bit is rescanned, and its ancestors are recomputed, which
+    /// takes `O(block_size + log n)` time instead of rebuilding the whole tree.
+    ///
+    /// The bit in `bit_vec` is not read, so it does not matter whether the vector already contains
+    /// the new value.
+    ///
+    /// # Panics
+    /// Panics if `block_size` is zero or `bit_index` is out of bounds. The `block_size` must be the
+    /// one the tree was built with, otherwise the tree is corrupted.
+    ///
+    /// [`excess_tree`]: MinMaxTree::excess_tree
+    pub(crate) fn update_bit(
+        &mut self,
+        bit_vec: &BitVec,
+        block_size: usize,
+        bit_index: usize,
+        new_value: bool,
+    ) {
+        assert!(block_size > 0, "block size must be positive");
+        assert!(bit_index < bit_vec.len(), "Index out of bounds");
+
+        let block = bit_index / block_size;
+        let start = block * block_size;
+        let end = bit_vec.len().min(start + block_size);
 
-            current_level_size /= 2;
-            current_level_start -= current_level_size;
+        let mut total_excess = 0;
+        let mut min_excess = i64::MAX;
+        let mut max_excess = i64::MIN;
+        for i in start..end {
+            let bit = if i == bit_index {
+                new_value
+            } else {
+                bit_vec.is_bit_set_unchecked(i)
+            };
+            total_excess += if bit { 1 } else { -1 };
+            min_excess = min_excess.min(total_excess);
+            max_excess = max_excess.max(total_excess);
         }
 
-        Self {
-            nodes: nodes.into_boxed_slice(),
+        // changing a single parenthesis changes the excess of the block by at most two
+        debug_assert!(
+            matches!(
+                total_excess - self.nodes[self.first_leaf() + block].total(),
+                -2 | 0 | 2
+            ),
+            "block size does not match the tree"
+        );
+
+        self.update_leaves(block, &[(total_excess, min_excess, max_excess)]);
+    }
+
+    /// Get the `(total, min, max)` excess of the root node, which covers the whole parenthesis
+    /// expression, or `None` if the tree is empty.
+    pub(crate) fn root_stats(&self) -> Option<(i64, i64, i64)> {
+        // the root is the first node in level order
+        self.level_order()
+            .next()
+            .map(|(_, root)| (root.total(), root.min(), root.max()))
+    }
+
+    /// Iterate over the nodes of the tree in level order, i.e. the order in which they are stored,
+    /// together with their indices.
+    /// The children of the node at index `i` are at indices `2i + 1` and `2i + 2`.
+    pub(crate) fn level_order(&self) -> impl Iterator<Item = (usize, &ExcessNode<E>)> + '_ {
+        self.nodes.iter().enumerate()
+    }
+
+    /// Get the number of nodes in the tree, including internal nodes that have no children.
+    pub(crate) fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get the number of leaves in the tree, which is the number of blocks of the parenthesis
+    /// expression. Returns 0 for an empty tree.
+    pub(crate) fn num_blocks(&self) -> usize {
+        if self.nodes.is_empty() {
+            0
+        } else {
+            self.nodes.len() - self.first_leaf()
+        }
+    }
+
+    /// Get the number of levels of the tree, including the leaf level. Returns 0 for an empty tree.
+    pub(crate) fn depth(&self) -> usize {
+        if self.nodes.is_empty() {
+            0
+        } else {
+            // the internal nodes form a complete binary tree with `first_leaf` nodes
+            (self.first_leaf() + 1).trailing_zeros() as usize + 1
         }
     }
 
     pub(crate) fn total_excess(&self, index: usize) -> i64 {
-        self.nodes[index].total
+        self.nodes[index].total()
     }
 
     pub(crate) fn min_excess(&self, index: usize) -> i64 {
-        self.nodes[index].min
+        self.nodes[index].min()
     }
 
     pub(crate) fn max_excess(&self, index: usize) -> i64 {
-        self.nodes[index].max
+        self.nodes[index].max()
     }
 
     pub(crate) fn parent(&self, index: NonZeroUsize) -> Option<usize> {
@@ -169,6 +603,7 @@ impl MinMaxTree {
 
     /// Get the index of the left sibling of the node at `index` if it exists
     #[allow(clippy::unused_self)] // self is used for consistency with other methods
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
     pub(crate) fn left_sibling(&self, index: NonZeroUsize) -> Option<NonZeroUsize> {
         if index.get() % 2 == 0 {
             // index is at least 2
@@ -211,6 +646,12 @@ impl MinMaxTree {
     ///   That is, if a query at index `i` seeks excess `x`, and between `i` and the end of the
     ///   block `j` there is excess `y`, then the relative excess is `x - y`.
     pub(crate) fn fwd_search(&self, begin: usize, relative_excess: i64) -> Option<(usize, i64)> {
+        // an empty tree has no blocks, and a tree with a single block has no other block to
+        // search, so the search must fail
+        if self.nodes.len() <= 2 {
+            return None;
+        }
+
         if begin + self.first_leaf() >= self.nodes.len() {
             return None;
         }
@@ -222,6 +663,40 @@ impl MinMaxTree {
         .map(|(node, relative_excess)| (node.get() - self.first_leaf(), relative_excess))
     }
 
+    /// Forward search for the leaf node that contains the next position with the given excess,
+    /// including the beginning block.
+    /// Unlike [`fwd_search`], the beginning block is checked first, and returned if its `min` and
+    /// `max` excess bracket the target. Otherwise, the search continues like [`fwd_search`].
+    ///
+    /// # Parameters
+    /// - `begin`: The index of the leaf block to start the search from (the first leaf is indexed with 0).
+    /// - `relative_excess`: The excess to search for relative to the excess at the **start** of
+    ///   the block, not its end as in [`fwd_search`].
+    ///   That is, if a query at the first bit of the block seeks the excess `x` relative to the
+    ///   excess before that bit, the relative excess is `x`.
+    ///
+    /// The returned residual excess is relative to the start of the found block, as in
+    /// [`fwd_search`], so if the beginning block is returned, the residual is `relative_excess`.
+    ///
+    /// [`fwd_search`]: MinMaxTree::fwd_search
+    #[allow(dead_code)] // building block for callers that resolve in the start block, no caller yet
+    pub(crate) fn fwd_search_inclusive(
+        &self,
+        begin: usize,
+        relative_excess: i64,
+    ) -> Option<(usize, i64)> {
+        if self.nodes.is_empty() || begin + self.first_leaf() >= self.nodes.len() {
+            return None;
+        }
+
+        let leaf = begin + self.first_leaf();
+        if self.min_excess(leaf) <= relative_excess && relative_excess <= self.max_excess(leaf) {
+            Some((begin, relative_excess))
+        } else {
+            self.fwd_search(begin, relative_excess - self.total_excess(leaf))
+        }
+    }
+
     /// Backward search for the leaf node that contains the closest position with the given excess.
     /// The search only searches for the block, not the exact position.
     /// It further assumes that the beginning block does not contain the position, so the search
@@ -233,6 +708,12 @@ impl MinMaxTree {
     ///   That is, if a query at index `i` seeks excess `x`, and between `i` and the start of the
     ///   block `j` there is excess `y`, then the relative excess is `x - y`.
     pub(crate) fn bwd_search(&self, begin: usize, relative_excess: i64) -> Option<(usize, i64)> {
+        // an empty tree has no blocks, and a tree with a single block has no other block to
+        // search, so the search must fail
+        if self.nodes.len() <= 2 {
+            return None;
+        }
+
         if begin + self.first_leaf() >= self.nodes.len() {
             return None;
         }
@@ -431,92 +912,627 @@ impl MinMaxTree {
         }
     }
 
-    /// Returns the number of bytes used on the heap for this structure. This does not include
-    /// allocated space that is not used (e.g. by the allocation behavior of `Vec`).
-    #[must_use]
-    pub fn heap_size(&self) -> usize {
-        self.nodes.len() * size_of::<ExcessNode>()
+    /// Find the leaf block in the range `[begin, end)` of leaf indices (the first leaf is indexed
+    /// with 0) in which the excess, relative to the start of block `begin`, reaches its minimum
+    /// over the whole range. If multiple blocks reach the minimum, the leftmost one is returned.
+    ///
+    /// The range is decomposed into the canonical subtrees covering it (see [`range_min_node`]).
+    /// Afterwards, the search descends from the subtree containing the minimum into the leftmost
+    /// leaf that reaches it. Both steps take `O(log n)` time.
+    ///
+    /// # Panics
+    /// The range must be non-empty and contain only existing leaves, otherwise the function may
+    /// panic or return a meaningless result.
+    ///
+    /// [`range_min_node`]: MinMaxTree::range_min_node
+    pub(crate) fn range_argmin_block(&self, begin: usize, end: usize) -> usize {
+        // descend into the leftmost child that reaches the minimum
+        let (target, mut node, mut prefix) = self.range_min_node(begin, end);
+        while !self.is_leaf(node) {
+            let left_child = node * 2 + 1;
+            if prefix + self.nodes[left_child].min() == target {
+                node = left_child;
+            } else {
+                prefix += self.nodes[left_child].total();
+                node = left_child + 1;
+            }
+        }
+
+        node - self.first_leaf()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::BitVec;
+    /// Get the minimum excess over the leaf blocks in the range `[block_lo, block_hi)` (the first
+    /// leaf is indexed with 0), relative to the start of block `block_lo`.
+    /// The minimum is combined from the canonical subtrees covering the range the same way the
+    /// `min` values of two children are combined into their parent, so it takes `O(log n)` time.
+    ///
+    /// # Panics
+    /// The range must be non-empty and contain only existing leaves, otherwise the function may
+    /// panic or return a meaningless result.
+    #[allow(dead_code)] // only used by `enclosed_pairs`
+    pub(crate) fn range_min_excess(&self, block_lo: usize, block_hi: usize) -> i64 {
+        self.range_min_node(block_lo, block_hi).0
+    }
 
-    #[test]
-    fn test_simple_excess_tree() {
-        #[rustfmt::skip]
-        let bv = BitVec::from_bits(&[
-            1, 1, 1, 0, 0, 1, 1, 1,
-            0, 1, 0, 1, 1, 1, 0, 0,
-            1, 0, 0, 1, 0, 0, 0, 0,
-        ]);
+    /// Decompose the range `[begin, end)` of leaf indices into the canonical subtrees covering it,
+    /// and combine them from left to right while tracking the total excess before each subtree.
+    /// Returns the minimum excess relative to the start of block `begin`, the leftmost canonical
+    /// subtree reaching it, and the excess before that subtree.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
+    fn range_min_node(&self, begin: usize, end: usize) -> (i64, usize, i64) {
+        debug_assert!(begin < end, "range of blocks must be non-empty");
+        debug_assert!(
+            end + self.first_leaf() <= self.nodes.len(),
+            "range of blocks is out of bounds"
+        );
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        // canonical subtrees from the left and right boundary, each in the order of collection
+        let mut left_nodes = Vec::new();
+        let mut right_nodes = Vec::new();
+        let mut left = begin + self.first_leaf();
+        let mut right = end - 1 + self.first_leaf();
+        loop {
+            if left == right {
+                left_nodes.push(left);
+                break;
+            }
 
-        // three internal nodes, three leaves
-        assert_eq!(tree.nodes.len(), 6);
+            // a right child at the left boundary and a left child at the right boundary are not
+            // covered completely by their parents
+            if left % 2 == 0 {
+                left_nodes.push(left);
+                left += 1;
+            }
+            if right % 2 == 1 {
+                right_nodes.push(right);
+                right -= 1;
+            }
+            if left > right {
+                break;
+            }
 
-        // leaf nodes
-        assert_eq!(tree.nodes[3].total, 4);
-        assert_eq!(tree.nodes[3].min, 1);
-        assert_eq!(tree.nodes[3].max, 4);
+            left = (left - 1) / 2;
+            right = (right - 1) / 2;
+        }
 
-        assert_eq!(tree.nodes[4].total, 0);
-        assert_eq!(tree.nodes[4].min, -1);
-        assert_eq!(tree.nodes[4].max, 2);
+        let mut prefix = 0;
+        let mut best = (i64::MAX, 0, 0);
+        for &node in left_nodes.iter().chain(right_nodes.iter().rev()) {
+            if prefix + self.nodes[node].min() < best.0 {
+                best = (prefix + self.nodes[node].min(), node, prefix);
+            }
+            prefix += self.nodes[node].total();
+        }
 
-        assert_eq!(tree.nodes[5].total, -4);
-        assert_eq!(tree.nodes[5].min, -4);
-        assert_eq!(tree.nodes[5].max, 1);
+        best
+    }
 
-        // root node
-        assert_eq!(tree.nodes[0].total, 0); // the tree should be balanced
-        assert_eq!(tree.nodes[0].min, 0);
-        assert_eq!(tree.nodes[0].max, 6);
+    /// Get the total excess of all blocks strictly before the block `block_index` (the first
+    /// block is indexed with 0), i.e. the excess at the start of that block.
+    /// The sum is accumulated on the path from the leaf to the root by adding the totals of all
+    /// left siblings along the way, so it takes `O(log n)` time.
+    ///
+    /// A `block_index` of 0 results in 0, and a `block_index` equal to the number of leaves
+    /// results in the total excess of the root.
+    ///
+    /// # Panics
+    /// The `block_index` must not exceed the number of leaves, otherwise the function may panic or
+    /// return a meaningless result.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)] // `is_multiple_of` postdates the MSRV
+    #[allow(dead_code)] // building block for global depth queries, no caller yet
+    pub(crate) fn excess_prefix(&self, block_index: usize) -> i64 {
+        if block_index == 0 || self.nodes.is_empty() {
+            return 0;
+        }
 
-        // left child of the root
-        assert_eq!(tree.nodes[1].total, 4);
-        assert_eq!(tree.nodes[1].min, 1);
-        assert_eq!(tree.nodes[1].max, 6);
+        let mut node = block_index + self.first_leaf();
+        debug_assert!(node <= self.nodes.len(), "block index is out of bounds");
+        if node == self.nodes.len() {
+            return self.nodes[0].total();
+        }
 
-        // right child of the root
-        assert_eq!(tree.nodes[2].total, -4);
-        assert_eq!(tree.nodes[2].min, -4);
-        assert_eq!(tree.nodes[2].max, 1);
+        let mut prefix = 0;
+        while node > 0 {
+            // right children are stored at even indices, and their left sibling precedes them
+            if node % 2 == 0 {
+                prefix += self.nodes[node - 1].total();
+            }
+            node = (node - 1) / 2;
+        }
+
+        prefix
     }
 
-    #[test]
-    fn test_empty_excess_tree() {
-        let bv = BitVec::new();
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+    /// Materialize [`excess_prefix`] for every block index in `0..=num_blocks()` in a single pass
+    /// over the leaves, so the excess at block boundaries can be looked up in constant time.
+    /// The array has `num_blocks() + 1` entries, starting with 0 and ending with the total excess
+    /// of the expression. Its size is reported by [`prefix_excess_heap_size`].
+    ///
+    /// [`excess_prefix`]: MinMaxTree::excess_prefix
+    /// [`prefix_excess_heap_size`]: MinMaxTree::prefix_excess_heap_size
+    #[allow(dead_code)] // building block for query-heavy callers, no caller yet
+    pub(crate) fn build_prefix_excess(&self) -> Box<[i64]> {
+        let mut prefix = Vec::with_capacity(self.num_blocks() + 1);
+        prefix.push(0);
+        for (_, total, _, _) in self.leaves() {
+            prefix.push(prefix[prefix.len() - 1] + total);
+        }
+        prefix.into_boxed_slice()
+    }
 
-        assert_eq!(tree.nodes.len(), 0);
+    /// Get the number of bytes on the heap that the array built by [`build_prefix_excess`]
+    /// occupies, without building it.
+    ///
+    /// [`build_prefix_excess`]: MinMaxTree::build_prefix_excess
+    #[allow(dead_code)] // companion of `build_prefix_excess`
+    pub(crate) fn prefix_excess_heap_size(&self) -> usize {
+        (self.num_blocks() + 1) * size_of::<i64>()
     }
 
-    #[test]
-    fn test_excess_tree_navigation() {
-        // expected tree layout:
-        //      0
-        //    /  \
-        //   1    2
-        //   /\  /\
-        //  3  4 5 6
-        //  /\/\/\/\
-        // 7 8 9 10 11 12 - -
-        let bv = BitVec::from_bits(&[0; 48]);
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+    /// Count the parenthesis pairs of an expression of `len` bits that both open and close within
+    /// the blocks `[block_lo, block_hi)` (the first block is indexed with 0).
+    /// Pairs that cross the boundary of the range are not counted.
+    ///
+    /// Within the range, every closing parenthesis that drops the excess below all earlier
+    /// values is matched outside the range, and so is every opening parenthesis that is still open
+    /// at the end. Hence, the count follows from the length, the total excess, and the minimum
+    /// excess of the range, which are combined from the tree in `O(log n)` time. The bits are
+    /// never scanned, the length of the expression is only needed for the length of the last
+    /// block.
+    ///
+    /// # Panics
+    /// The range must contain only existing blocks, and `block_size` must match the tree,
+    /// otherwise the function may panic or return a meaningless result.
+    #[allow(dead_code)] // statistics helper, no caller yet
+    pub(crate) fn enclosed_pairs(
+        &self,
+        len: usize,
+        block_size: usize,
+        block_lo: usize,
+        block_hi: usize,
+    ) -> usize {
+        if block_lo >= block_hi {
+            return 0;
+        }
 
-        assert_eq!(tree.nodes.len(), 13); // 6 leaves + 7 internal nodes
+        let range_len = (block_hi * block_size).min(len) - block_lo * block_size;
+        let total = self.excess_prefix(block_hi) - self.excess_prefix(block_lo);
+        let unmatched_closes = -self.range_min_excess(block_lo, block_hi).min(0);
 
-        // check root
-        assert_eq!(tree.left_child(0), NonZeroUsize::new(1));
-        assert_eq!(tree.right_child(0), NonZeroUsize::new(2));
+        // the opening parentheses are (range_len + total) / 2, of which total + unmatched_closes
+        // remain open at the end of the range
+        ((range_len as i64 - total) / 2 - unmatched_closes) as usize
+    }
 
-        // check full nodes
-        for node in 1..=5 {
-            assert_eq!(tree.left_child(node), NonZeroUsize::new(node * 2 + 1));
+    /// Count the leaves (i.e. blocks) whose minimum excess is below `threshold`.
+    /// Note that the minimum excess of a leaf is relative to the start of its block, not to the
+    /// start of the parenthesis expression, so this characterizes the shape of the blocks,
+    /// not the depth of the nodes in them.
+    #[allow(dead_code)] // diagnostics, only called from tests and while tuning
+    pub(crate) fn count_leaves_below(&self, threshold: i64) -> usize {
+        self.leaves()
+            .filter(|&(_, _, min, _)| min < threshold)
+            .count()
+    }
+
+    /// Iterate over the leaves (i.e. blocks) in order, yielding `(block_index, total, min, max)`
+    /// for each leaf, where `min` and `max` are relative to the start of the block.
+    /// Only the leaves are yielded, not the internal nodes without children that pad the tree.
+    pub(crate) fn leaves(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (usize, i64, i64, i64)> + ExactSizeIterator + '_ {
+        self.nodes[self.nodes.len() - self.num_blocks()..]
+            .iter()
+            .enumerate()
+            .map(|(block, leaf)| (block, leaf.total(), leaf.min(), leaf.max()))
+    }
+
+    /// Render the tree in the Graphviz DOT format for debugging.
+    /// Each node is labeled with its index and its `total/min/max` excess values, and each edge
+    /// points from a parent to its children.
+    /// Leaves are drawn filled to distinguish them from internal nodes.
+    #[allow(dead_code)] // debugging aid, only called from tests and while debugging
+    pub(crate) fn to_dot(&self) -> String {
+        use std::fmt::Write as _;
+
+        let mut dot = String::from("digraph MinMaxTree {\n    node [shape=box];\n");
+
+        for (index, node) in self.level_order() {
+            let style = if self.is_leaf(index) {
+                ", style=filled, fillcolor=lightgray"
+            } else {
+                ""
+            };
+            // writing to a string cannot fail
+            let _ = writeln!(
+                dot,
+                "    n{index} [label=\"{index}: {}/{}/{}\"{style}];",
+                node.total(),
+                node.min(),
+                node.max()
+            );
+        }
+
+        for (index, _) in self.level_order() {
+            for child in [self.left_child(index), self.right_child(index)]
+                .into_iter()
+                .flatten()
+            {
+                let _ = writeln!(dot, "    n{index} -> n{child};");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Write the tree to `writer` in a compact binary layout: the number of nodes as a
+    /// little-endian `u64`, followed by the `total`, `min`, and `max` excess of each node in level
+    /// order, each as a little-endian `i64`.
+    /// The layout doesn't depend on serde or on the excess type of the tree, so a tree can be read
+    /// back with [`deserialize_from`] into any excess type its values fit into.
+    ///
+    /// Every value is written individually, so `writer` should be buffered.
+    ///
+    /// # Errors
+    /// Returns any error that occurs while writing.
+    ///
+    /// [`deserialize_from`]: MinMaxTree::deserialize_from
+    pub(crate) fn serialize_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.nodes.len() as u64).to_le_bytes())?;
+        for node in &self.nodes {
+            for value in [node.total(), node.min(), node.max()] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a tree written by [`serialize_to`] from `reader`.
+    ///
+    /// Every value is read individually, so `reader` should be buffered.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`InvalidData`] if the number of nodes doesn't match the shape of
+    /// a min-max tree, or if a value doesn't fit into the excess type, and any error that occurs
+    /// while reading (including [`UnexpectedEof`] if the input is truncated).
+    ///
+    /// [`serialize_to`]: MinMaxTree::serialize_to
+    /// [`InvalidData`]: io::ErrorKind::InvalidData
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    pub(crate) fn deserialize_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut buffer = [0u8; 8];
+        let mut read_value = |reader: &mut R| -> io::Result<u64> {
+            reader.read_exact(&mut buffer)?;
+            Ok(u64::from_le_bytes(buffer))
+        };
+
+        let num_nodes = usize::try_from(read_value(reader)?)
+            .ok()
+            .filter(|&num_nodes| num_nodes == 0 || Self::num_leaves_of(num_nodes).is_some())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "number of nodes doesn't match a min-max tree",
+                )
+            })?;
+
+        // the number of nodes is not trusted for preallocation, as it may be corrupted
+        let mut nodes = Vec::new();
+        for _ in 0..num_nodes {
+            let total = read_value(reader)? as i64;
+            let min = read_value(reader)? as i64;
+            let max = read_value(reader)? as i64;
+            nodes.push(ExcessNode::try_new(total, min, max).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "excess value doesn't fit into the excess type",
+                )
+            })?);
+        }
+
+        Ok(Self {
+            nodes: nodes.into_boxed_slice(),
+        })
+    }
+
+    /// Copy the tree `tree` with excess type `F` into a tree with excess type `E`, or return
+    /// `None` if one of its values doesn't fit into `E`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn try_from_tree<F: ExcessInt>(tree: &MinMaxTree<F>) -> Option<Self> {
+        Some(Self {
+            nodes: tree
+                .nodes
+                .iter()
+                .map(|node| ExcessNode::try_new(node.total(), node.min(), node.max()))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Get the number of leaves of a non-empty tree with `num_nodes` nodes, or `None` if no tree
+    /// has that many nodes.
+    /// A tree with `l` leaves has `max(1, l.next_power_of_two() - 1)` internal nodes, as allocated
+    /// by [`rebuild_from_blocks`].
+    ///
+    /// [`rebuild_from_blocks`]: MinMaxTree::rebuild_from_blocks
+    pub(crate) fn num_leaves_of(num_nodes: usize) -> Option<usize> {
+        let mut leaves_bound = 1usize;
+        loop {
+            let num_internal_nodes = max(1, leaves_bound - 1);
+            if num_internal_nodes >= num_nodes {
+                return None;
+            }
+
+            let num_leaves = num_nodes - num_internal_nodes;
+            if num_leaves.next_power_of_two() == leaves_bound {
+                return Some(num_leaves);
+            }
+            leaves_bound = leaves_bound.checked_mul(2)?;
+        }
+    }
+
+    /// Returns the number of bytes used on the heap for this structure. This does not include
+    /// allocated space that is not used (e.g. by the allocation behavior of `Vec`).
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        let breakdown = self.size_breakdown();
+        breakdown.internal_nodes_bytes + breakdown.leaf_nodes_bytes
+    }
+
+    /// Split the heap size of the tree into the internal nodes and the leaves.
+    pub(crate) fn size_breakdown(&self) -> SizeBreakdown {
+        let internal_nodes = if self.nodes.is_empty() {
+            0
+        } else {
+            self.first_leaf()
+        };
+
+        SizeBreakdown {
+            internal_nodes_bytes: internal_nodes * size_of::<ExcessNode<E>>(),
+            leaf_nodes_bytes: (self.num_nodes() - internal_nodes) * size_of::<ExcessNode<E>>(),
+            node_count: self.num_nodes(),
+        }
+    }
+}
+
+/// Evaluate `$build` with the type alias `$tree` bound to the min-max tree with the narrowest
+/// excess type that fits an expression of `$len` bits, and wrap the result in the matching
+/// [`AdaptiveMinMaxTree`] variant.
+macro_rules! narrowest_tree {
+    ($len:expr, $tree:ident => $build:expr) => {{
+        let len: usize = $len;
+        if len <= <i16 as ExcessInt>::MAX_LEN {
+            type $tree = MinMaxTree<i16>;
+            AdaptiveMinMaxTree::I16($build)
+        } else if len <= <i32 as ExcessInt>::MAX_LEN {
+            type $tree = MinMaxTree<i32>;
+            AdaptiveMinMaxTree::I32($build)
+        } else {
+            type $tree = MinMaxTree<i64>;
+            AdaptiveMinMaxTree::I64($build)
+        }
+    }};
+}
+
+/// Evaluate `$body` with `$tree` bound to the min-max tree inside an [`AdaptiveMinMaxTree`].
+macro_rules! dispatch {
+    ($self:expr, $tree:ident => $body:expr) => {
+        match $self {
+            AdaptiveMinMaxTree::I16($tree) => $body,
+            AdaptiveMinMaxTree::I32($tree) => $body,
+            AdaptiveMinMaxTree::I64($tree) => $body,
+        }
+    };
+}
+
+/// A min-max tree whose excess type is the narrowest one that fits the length of the
+/// parenthesis expression. This is the tree used by [`BpTree`].
+///
+/// The length of the expression never changes after construction, so the excess type never
+/// has to be widened, even if bits are flipped with [`update_bit`].
+///
+/// [`BpTree`]: crate::trees::bp::BpTree
+/// [`update_bit`]: AdaptiveMinMaxTree::update_bit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AdaptiveMinMaxTree {
+    I16(MinMaxTree<i16>),
+    I32(MinMaxTree<i32>),
+    I64(MinMaxTree<i64>),
+}
+
+impl AdaptiveMinMaxTree {
+    /// Build the min-max tree over `bit_vec` as [`MinMaxTree::excess_tree`] does.
+    pub(crate) fn excess_tree(bit_vec: &BitVec, block_size: usize) -> Self {
+        narrowest_tree!(bit_vec.len(), Tree => Tree::excess_tree(bit_vec, block_size))
+    }
+
+    /// Build the min-max tree over `bit_vec` as [`MinMaxTree::excess_tree_parallel`] does.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn excess_tree_parallel(bit_vec: &BitVec, block_size: usize) -> Self {
+        narrowest_tree!(bit_vec.len(), Tree => Tree::excess_tree_parallel(bit_vec, block_size))
+    }
+
+    /// Build the min-max tree over `bit_vec` as [`MinMaxTree::try_excess_tree`] does.
+    ///
+    /// # Errors
+    /// Returns an [`ExcessError`] if `bit_vec` is not a balanced parenthesis expression.
+    pub(crate) fn try_excess_tree(
+        bit_vec: &BitVec,
+        block_size: usize,
+    ) -> Result<Self, ExcessError> {
+        Ok(narrowest_tree!(bit_vec.len(), Tree => Tree::try_excess_tree(bit_vec, block_size)?))
+    }
+
+    /// Read a tree over an expression of `len` bits, as written by [`serialize_to`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`MinMaxTree::deserialize_from`].
+    ///
+    /// [`serialize_to`]: AdaptiveMinMaxTree::serialize_to
+    pub(crate) fn deserialize_from<R: Read>(reader: &mut R, len: usize) -> io::Result<Self> {
+        Ok(narrowest_tree!(len, Tree => Tree::deserialize_from(reader)?))
+    }
+
+    /// Copy the tree into a tree over an expression of `len` bits with the narrowest excess type,
+    /// or return `None` if one of its values doesn't fit into that type.
+    #[cfg(feature = "serde")]
+    pub(crate) fn try_from_wide(tree: &MinMaxTree64, len: usize) -> Option<Self> {
+        Some(narrowest_tree!(len, Tree => Tree::try_from_tree(tree)?))
+    }
+
+    /// Copy the tree into a tree with `i64` excess values, which is the layout of all trees
+    /// before the excess type was narrowed.
+    #[cfg(feature = "serde")]
+    pub(crate) fn to_wide(&self) -> MinMaxTree64 {
+        dispatch!(self, tree => MinMaxTree64::try_from_tree(tree))
+            .expect("every excess value fits into i64")
+    }
+
+    /// Write the tree to `writer` as [`MinMaxTree::serialize_to`] does. The layout doesn't
+    /// depend on the excess type.
+    ///
+    /// # Errors
+    /// Returns any error that occurs while writing.
+    pub(crate) fn serialize_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        dispatch!(self, tree => tree.serialize_to(writer))
+    }
+
+    /// See [`MinMaxTree::update_bit`].
+    pub(crate) fn update_bit(
+        &mut self,
+        bit_vec: &BitVec,
+        block_size: usize,
+        bit_index: usize,
+        new_value: bool,
+    ) {
+        dispatch!(self, tree => tree.update_bit(bit_vec, block_size, bit_index, new_value));
+    }
+
+    /// See [`MinMaxTree::root_stats`].
+    pub(crate) fn root_stats(&self) -> Option<(i64, i64, i64)> {
+        dispatch!(self, tree => tree.root_stats())
+    }
+
+    /// See [`MinMaxTree::num_nodes`].
+    pub(crate) fn num_nodes(&self) -> usize {
+        dispatch!(self, tree => tree.num_nodes())
+    }
+
+    /// See [`MinMaxTree::num_blocks`].
+    pub(crate) fn num_blocks(&self) -> usize {
+        dispatch!(self, tree => tree.num_blocks())
+    }
+
+    /// See [`MinMaxTree::depth`].
+    pub(crate) fn depth(&self) -> usize {
+        dispatch!(self, tree => tree.depth())
+    }
+
+    /// See [`MinMaxTree::fwd_search`].
+    pub(crate) fn fwd_search(&self, begin: usize, relative_excess: i64) -> Option<(usize, i64)> {
+        dispatch!(self, tree => tree.fwd_search(begin, relative_excess))
+    }
+
+    /// See [`MinMaxTree::bwd_search`].
+    pub(crate) fn bwd_search(&self, begin: usize, relative_excess: i64) -> Option<(usize, i64)> {
+        dispatch!(self, tree => tree.bwd_search(begin, relative_excess))
+    }
+
+    /// See [`MinMaxTree::range_argmin_block`].
+    pub(crate) fn range_argmin_block(&self, begin: usize, end: usize) -> usize {
+        dispatch!(self, tree => tree.range_argmin_block(begin, end))
+    }
+
+    /// Returns the number of bytes used on the heap for this structure, which depends on the
+    /// excess type.
+    pub(crate) fn heap_size(&self) -> usize {
+        dispatch!(self, tree => tree.heap_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BitVec;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_simple_excess_tree() {
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 1, 0, 0, 1, 1, 1,
+            0, 1, 0, 1, 1, 1, 0, 0,
+            1, 0, 0, 1, 0, 0, 0, 0,
+        ]);
+
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+        // three internal nodes, three leaves
+        assert_eq!(tree.nodes.len(), 6);
+
+        // leaf nodes
+        assert_eq!(tree.nodes[3].total, 4);
+        assert_eq!(tree.nodes[3].min, 1);
+        assert_eq!(tree.nodes[3].max, 4);
+
+        assert_eq!(tree.nodes[4].total, 0);
+        assert_eq!(tree.nodes[4].min, -1);
+        assert_eq!(tree.nodes[4].max, 2);
+
+        assert_eq!(tree.nodes[5].total, -4);
+        assert_eq!(tree.nodes[5].min, -4);
+        assert_eq!(tree.nodes[5].max, 1);
+
+        // root node
+        assert_eq!(tree.nodes[0].total, 0); // the tree should be balanced
+        assert_eq!(tree.nodes[0].min, 0);
+        assert_eq!(tree.nodes[0].max, 6);
+
+        // left child of the root
+        assert_eq!(tree.nodes[1].total, 4);
+        assert_eq!(tree.nodes[1].min, 1);
+        assert_eq!(tree.nodes[1].max, 6);
+
+        // right child of the root
+        assert_eq!(tree.nodes[2].total, -4);
+        assert_eq!(tree.nodes[2].min, -4);
+        assert_eq!(tree.nodes[2].max, 1);
+
+        assert_eq!(tree.root_stats(), Some((0, 0, 6)));
+    }
+
+    #[test]
+    fn test_empty_excess_tree() {
+        let bv = BitVec::new();
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+        assert_eq!(tree.nodes.len(), 0);
+        assert_eq!(tree.root_stats(), None);
+    }
+
+    #[test]
+    fn test_excess_tree_navigation() {
+        // expected tree layout:
+        //      0
+        //    /  \
+        //   1    2
+        //   /\  /\
+        //  3  4 5 6
+        //  /\/\/\/\
+        // 7 8 9 10 11 12 - -
+        let bv = BitVec::from_bits(&[0; 48]);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+        assert_eq!(tree.nodes.len(), 13); // 6 leaves + 7 internal nodes
+
+        // check root
+        assert_eq!(tree.left_child(0), NonZeroUsize::new(1));
+        assert_eq!(tree.right_child(0), NonZeroUsize::new(2));
+
+        // check full nodes
+        for node in 1..=5 {
+            assert_eq!(tree.left_child(node), NonZeroUsize::new(node * 2 + 1));
             assert_eq!(tree.right_child(node), NonZeroUsize::new(node * 2 + 2));
         }
 
@@ -572,7 +1588,7 @@ mod tests {
     #[test]
     fn test_empty_tree_navigation() {
         let bv = BitVec::new();
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 0);
 
@@ -592,7 +1608,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 6);
         assert_eq!(tree.total_excess(0), 0); // tree should be balanced
@@ -633,7 +1649,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 12);
         assert_eq!(tree.total_excess(0), 0); // tree should be balanced
@@ -658,7 +1674,7 @@ mod tests {
             0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 4);
+        let tree = MinMaxTree64::excess_tree(&bv, 4);
 
         // if the relative excess is calculated wrong, it will find block 5, since -1 + 2 = 1,
         // which is the max excess in block 5. Correct calculation of relative excess is -1 - 2 = -3
@@ -676,7 +1692,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 6);
         assert_eq!(tree.total_excess(0), 0); // tree should be balanced
@@ -717,7 +1733,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 12);
         assert_eq!(tree.total_excess(0), 0); // tree should be balanced
@@ -742,7 +1758,7 @@ mod tests {
             0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 4);
+        let tree = MinMaxTree64::excess_tree(&bv, 4);
 
         let block = tree.bwd_search(3, -4);
         assert!(block.is_some());
@@ -757,7 +1773,7 @@ mod tests {
             0, 0, 0, 0, 0, 0
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 3);
 
@@ -774,25 +1790,872 @@ mod tests {
     fn test_single_block() {
         let bv = BitVec::from_bits(&[1, 1, 1, 1, 0, 0, 0, 0]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         assert_eq!(tree.nodes.len(), 2);
     }
 
+    #[test]
+    fn test_search_empty_and_single_block() {
+        let tree = MinMaxTree64::excess_tree(&BitVec::new(), 8);
+        for excess in -2..=2 {
+            assert_eq!(tree.fwd_search(0, excess), None);
+            assert_eq!(tree.bwd_search(0, excess), None);
+            assert_eq!(tree.fwd_search(1, excess), None);
+            assert_eq!(tree.bwd_search(1, excess), None);
+        }
+
+        // full and incomplete single blocks
+        for bv in [
+            BitVec::from_bits(&[1, 1, 1, 1, 0, 0, 0, 0]),
+            BitVec::from_bits(&[1, 1, 0, 0]),
+            BitVec::from_bits(&[1]),
+        ] {
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+            assert_eq!(tree.nodes.len(), 2);
+            for excess in -10..=10 {
+                assert_eq!(tree.fwd_search(0, excess), None);
+                assert_eq!(tree.bwd_search(0, excess), None);
+                assert_eq!(tree.fwd_search(1, excess), None);
+                assert_eq!(tree.bwd_search(1, excess), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_order() {
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 0, 1, 1, 0, 0, 1,
+            1, 0, 0, 1, 0, 0,
+        ]);
+
+        for block_size in [1, 3, 8, 14, 20] {
+            let tree = MinMaxTree64::excess_tree(&bv, block_size);
+            let nodes = tree.level_order().collect::<Vec<_>>();
+
+            assert_eq!(nodes.len(), tree.num_nodes());
+            for (expected_index, &(index, node)) in nodes.iter().enumerate() {
+                assert_eq!(index, expected_index);
+                assert_eq!(node, &tree.nodes[index]);
+            }
+        }
+
+        let tree = MinMaxTree64::excess_tree(&BitVec::new(), 8);
+        assert_eq!(tree.level_order().count(), 0);
+        assert_eq!(tree.num_nodes(), 0);
+    }
+
+    #[test]
+    fn test_count_leaves_below() {
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 1, 0, 0, 1, 1, 1,
+            0, 1, 0, 1, 1, 1, 0, 0,
+            1, 0, 0, 1, 0, 0, 0, 0,
+        ]);
+
+        // the relative minima of the leaves are 1, -1, and -4
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+        assert_eq!(tree.count_leaves_below(-4), 0);
+        assert_eq!(tree.count_leaves_below(-3), 1);
+        assert_eq!(tree.count_leaves_below(0), 2);
+        assert_eq!(tree.count_leaves_below(2), 3);
+
+        let tree = MinMaxTree64::excess_tree(&BitVec::new(), 8);
+        assert_eq!(tree.count_leaves_below(0), 0);
+    }
+
+    #[test]
+    fn test_range_argmin_block() {
+        // leftmost block in the range whose running excess reaches the minimum, computed by a
+        // scan over the block statistics
+        fn naive(blocks: &[(i64, i64, i64)], begin: usize, end: usize) -> usize {
+            let mut prefix = 0;
+            let mut best = (i64::MAX, begin);
+            for (block, &(total, min, _)) in blocks.iter().enumerate().take(end).skip(begin) {
+                if prefix + min < best.0 {
+                    best = (prefix + min, block);
+                }
+                prefix += total;
+            }
+            best.1
+        }
+
+        let mut rng = StdRng::from_seed([18; 32]);
+        for num_blocks in [1, 2, 3, 5, 8, 13, 16, 17] {
+            let mut bv = BitVec::new();
+            for _ in 0..num_blocks * 8 {
+                bv.append(rng.gen_bool(0.5));
+            }
+
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+            let blocks = (0..num_blocks)
+                .map(|block| {
+                    let mut excess = 0;
+                    let mut min = i64::MAX;
+                    let mut max = i64::MIN;
+                    for i in block * 8..(block + 1) * 8 {
+                        excess += if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+                        min = min.min(excess);
+                        max = max.max(excess);
+                    }
+                    (excess, min, max)
+                })
+                .collect::<Vec<_>>();
+
+            for begin in 0..num_blocks {
+                for end in begin + 1..=num_blocks {
+                    assert_eq!(
+                        tree.range_argmin_block(begin, end),
+                        naive(&blocks, begin, end),
+                        "range {begin}..{end} with {num_blocks} blocks"
+                    );
+                }
+            }
+        }
+
+        // the minimum is reached in the last two blocks, so the first of them is reported
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 1, 0, 0, 1, 1, 1,
+            0, 1, 0, 1, 1, 1, 0, 0,
+            1, 0, 0, 1, 0, 0, 0, 0,
+            1, 1, 1, 1, 0, 0, 0, 0,
+        ]);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+        assert_eq!(tree.range_argmin_block(0, 4), 2);
+        assert_eq!(tree.range_argmin_block(1, 4), 2);
+        assert_eq!(tree.range_argmin_block(2, 4), 2);
+        assert_eq!(tree.range_argmin_block(3, 4), 3);
+        assert_eq!(tree.range_argmin_block(0, 2), 0);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 1, 0, 0, 1, 1, 1,
+            0, 1, 0, 1, 1, 1, 0, 0,
+            1, 0, 0, 1, 0, 0, 0, 0,
+        ]);
+
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
+        let dot = tree.to_dot();
+
+        assert!(dot.starts_with("digraph MinMaxTree {"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches("[label=").count(), 6);
+        assert_eq!(dot.matches("fillcolor").count(), 3);
+        assert_eq!(dot.matches(" -> ").count(), 5);
+        assert!(dot.contains("n0 [label=\"0: 0/0/6\"];"));
+        assert!(dot.contains("n3 [label=\"3: 4/1/4\", style=filled, fillcolor=lightgray];"));
+        for edge in [
+            "n0 -> n1;",
+            "n0 -> n2;",
+            "n1 -> n3;",
+            "n1 -> n4;",
+            "n2 -> n5;",
+        ] {
+            assert!(dot.contains(edge), "missing edge {edge}");
+        }
+
+        let dot = MinMaxTree64::excess_tree(&BitVec::new(), 8).to_dot();
+        assert_eq!(dot.matches("[label=").count(), 0);
+    }
+
     #[test]
     fn test_leaf_calculation() {
         // test small tree
         let bv = BitVec::from_bits(&vec![0; 1000]);
-        let tree = MinMaxTree::excess_tree(&bv, 1200);
+        let tree = MinMaxTree64::excess_tree(&bv, 1200);
         assert_eq!(tree.first_leaf(), 1);
 
         // test very large tree
         let bv = BitVec::from_bits(&vec![0; 1000]);
-        let tree = MinMaxTree::excess_tree(&bv, 4);
+        let tree = MinMaxTree64::excess_tree(&bv, 4);
 
         assert_eq!(tree.first_leaf(), 255)
     }
 
+    #[test]
+    fn test_block_size_boundaries() {
+        fn num_leaves(tree: &MinMaxTree) -> usize {
+            tree.nodes.len() - tree.first_leaf()
+        }
+
+        #[rustfmt::skip]
+        let bv = BitVec::from_bits(&[
+            1, 1, 0, 1, 1, 0, 0, 1,
+            1, 0, 0, 1, 0, 0,
+        ]);
+        let len = bv.len();
+
+        // block size equal to the length, the whole vector is one leaf
+        let tree = MinMaxTree64::excess_tree(&bv, len);
+        assert_eq!(num_leaves(&tree), 1);
+        assert_eq!(tree.nodes.len(), 2);
+        assert_eq!(tree.nodes[1].total, 0);
+        assert_eq!(tree.nodes[1].min, 0);
+        assert_eq!(tree.nodes[1].max, 3);
+        assert_eq!(tree.nodes[0], tree.nodes[1]);
+
+        // block size larger than the length
+        let tree = MinMaxTree64::excess_tree(&bv, len + 1);
+        assert_eq!(num_leaves(&tree), 1);
+        assert_eq!(tree.nodes[1].total, 0);
+        assert_eq!(tree.nodes[1].min, 0);
+        assert_eq!(tree.nodes[1].max, 3);
+
+        // one leaf per bit
+        let tree = MinMaxTree64::excess_tree(&bv, 1);
+        assert_eq!(num_leaves(&tree), len);
+        for i in 0..len {
+            let leaf = &tree.nodes[tree.first_leaf() + i];
+            let expected = if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+            assert_eq!(leaf.total, expected);
+            assert_eq!(leaf.min, expected);
+            assert_eq!(leaf.max, expected);
+        }
+        assert_eq!(tree.nodes[0].total, 0);
+        assert_eq!(tree.nodes[0].min, 0);
+        assert_eq!(tree.nodes[0].max, 3);
+
+        // leaf count for all block sizes
+        for block_size in 1..=len + 2 {
+            let tree = MinMaxTree64::excess_tree(&bv, block_size);
+            assert_eq!(
+                num_leaves(&tree),
+                len.div_ceil(block_size),
+                "wrong number of leaves for block size {block_size}"
+            );
+            assert_eq!(tree.nodes[0].total, 0);
+            assert_eq!(tree.nodes[0].min, 0);
+            assert_eq!(tree.nodes[0].max, 3);
+        }
+    }
+
+    /// Build the nodes of the min-max tree over `block_stats` bottom-up, combining the children
+    /// of every internal node from the last one to the root. This is the reference the
+    /// incremental construction is compared against.
+    fn naive_nodes(block_stats: &[(i64, i64, i64)]) -> Vec<ExcessNode<i64>> {
+        if block_stats.is_empty() {
+            return Vec::new();
+        }
+
+        let num_internal_nodes = max(1, block_stats.len().next_power_of_two() - 1);
+        let mut nodes = vec![ExcessNode::default(); num_internal_nodes];
+        nodes.extend(
+            block_stats
+                .iter()
+                .map(|&(total, min, max)| ExcessNode { total, min, max }),
+        );
+
+        for index in (0..num_internal_nodes).rev() {
+            let (left, right) = (2 * index + 1, 2 * index + 2);
+            nodes[index] = if right < nodes.len() {
+                ExcessNode {
+                    total: nodes[left].total + nodes[right].total,
+                    min: nodes[left].min.min(nodes[left].total + nodes[right].min),
+                    max: nodes[left].max.max(nodes[left].total + nodes[right].max),
+                }
+            } else if left < nodes.len() {
+                nodes[left].clone()
+            } else {
+                ExcessNode::default()
+            };
+        }
+        nodes
+    }
+
+    #[test]
+    fn test_rebuild_from_blocks() {
+        let mut bv = BitVec::new();
+        for i in 0..1000u64 {
+            bv.append_bit(i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 63);
+        }
+
+        for block_size in [1, 7, 8, 64, 999, 1000, 2000] {
+            // scan each block bit by bit
+            let block_stats = (0..bv.len())
+                .step_by(block_size)
+                .map(|start| {
+                    let (mut excess, mut min, mut max) = (0, i64::MAX, i64::MIN);
+                    for i in start..bv.len().min(start + block_size) {
+                        excess += if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+                        min = min.min(excess);
+                        max = max.max(excess);
+                    }
+                    (excess, min, max)
+                })
+                .collect::<Vec<_>>();
+            let expected = naive_nodes(&block_stats);
+
+            let rebuilt = MinMaxTree64::rebuild_from_blocks(&block_stats);
+            assert_eq!(*rebuilt.nodes, *expected, "block size {block_size}");
+            let tree = MinMaxTree64::excess_tree(&bv, block_size);
+            assert_eq!(*tree.nodes, *expected, "block size {block_size}");
+        }
+
+        assert!(MinMaxTree64::rebuild_from_blocks(&[]).nodes.is_empty());
+    }
+
+    #[test]
+    fn test_update_leaves() {
+        let mut rng = StdRng::from_seed([25; 32]);
+        let random_stats = |rng: &mut StdRng| {
+            let total = rng.gen_range(-8..=8);
+            let min = rng.gen_range(-8..=total.min(1));
+            (total, min, rng.gen_range(total.max(-1)..=8))
+        };
+
+        for num_leaves in [1, 2, 3, 4, 5, 9, 16, 17, 30] {
+            let mut block_stats = (0..num_leaves)
+                .map(|_| random_stats(&mut rng))
+                .collect::<Vec<_>>();
+            let mut tree = MinMaxTree64::rebuild_from_blocks(&block_stats);
+            assert_eq!(*tree.nodes, *naive_nodes(&block_stats));
+
+            for _ in 0..20 {
+                let start = rng.gen_range(0..num_leaves);
+                let len = rng.gen_range(0..=num_leaves - start);
+                let update = (0..len).map(|_| random_stats(&mut rng)).collect::<Vec<_>>();
+                block_stats[start..start + len].copy_from_slice(&update);
+
+                tree.update_leaves(start, &update);
+                assert_eq!(
+                    *tree.nodes,
+                    *naive_nodes(&block_stats),
+                    "update of {len} leaves at {start} with {num_leaves} leaves"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_bit() {
+        let mut rng = StdRng::from_seed([26; 32]);
+
+        for (len, block_size) in [(1, 8), (7, 8), (64, 8), (100, 8), (300, 16), (129, 64)] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let mut tree = MinMaxTree64::excess_tree(&bv, block_size);
+
+            for _ in 0..50 {
+                let index = rng.gen_range(0..len);
+                let new_value = !bv.is_bit_set_unchecked(index);
+
+                // update the tree both before and after flipping the bit in the vector
+                if rng.gen_bool(0.5) {
+                    tree.update_bit(&bv, block_size, index, new_value);
+                    bv.flip_bit(index);
+                } else {
+                    bv.flip_bit(index);
+                    tree.update_bit(&bv, block_size, index, new_value);
+                }
+
+                assert_eq!(
+                    tree.nodes,
+                    MinMaxTree64::excess_tree(&bv, block_size).nodes,
+                    "flip of bit {index} with length {len} and block size {block_size}"
+                );
+            }
+
+            // setting a bit to its current value doesn't change the tree
+            let index = rng.gen_range(0..len);
+            tree.update_bit(&bv, block_size, index, bv.is_bit_set_unchecked(index));
+            assert_eq!(tree.nodes, MinMaxTree64::excess_tree(&bv, block_size).nodes);
+        }
+    }
+
+    #[test]
+    fn test_excess_prefix() {
+        let mut rng = StdRng::from_seed([28; 32]);
+
+        for num_leaves in [1, 2, 3, 4, 5, 7, 8, 9, 16, 17, 30] {
+            let mut bv = BitVec::with_capacity(num_leaves * 8);
+            for _ in 0..num_leaves * 8 - rng.gen_range(0..8) {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+            let mut expected = 0;
+            for block_index in 0..=num_leaves {
+                assert_eq!(
+                    tree.excess_prefix(block_index),
+                    expected,
+                    "block {block_index} of {num_leaves}"
+                );
+                if block_index < num_leaves {
+                    expected += tree.total_excess(tree.first_leaf() + block_index);
+                }
+            }
+            assert_eq!(tree.excess_prefix(num_leaves), tree.root_stats().unwrap().0);
+        }
+
+        assert_eq!(MinMaxTree64::default().excess_prefix(0), 0);
+    }
+
+    #[test]
+    fn test_narrow_excess_types() {
+        let mut rng = StdRng::from_seed([32; 32]);
+        let mut bv = BitVec::with_capacity(1000);
+        for _ in 0..1000 {
+            bv.append(rng.gen_bool(0.5));
+        }
+
+        let tree64 = MinMaxTree64::excess_tree(&bv, 16);
+        let tree32 = MinMaxTree::<i32>::excess_tree(&bv, 16);
+        let tree16 = MinMaxTree::<i16>::excess_tree(&bv, 16);
+
+        for (index, node) in tree64.level_order() {
+            let stats = (node.total(), node.min(), node.max());
+            assert_eq!(
+                stats,
+                (
+                    tree32.total_excess(index),
+                    tree32.min_excess(index),
+                    tree32.max_excess(index)
+                )
+            );
+            assert_eq!(
+                stats,
+                (
+                    tree16.total_excess(index),
+                    tree16.min_excess(index),
+                    tree16.max_excess(index)
+                )
+            );
+        }
+
+        for block in 0..1000usize.div_ceil(16) {
+            for relative_excess in -4..=4 {
+                let expected = tree64.fwd_search(block, relative_excess);
+                assert_eq!(tree32.fwd_search(block, relative_excess), expected);
+                assert_eq!(tree16.fwd_search(block, relative_excess), expected);
+
+                let expected = tree64.bwd_search(block, relative_excess);
+                assert_eq!(tree32.bwd_search(block, relative_excess), expected);
+                assert_eq!(tree16.bwd_search(block, relative_excess), expected);
+            }
+        }
+
+        assert_eq!(tree32.heap_size() * 2, tree64.heap_size());
+        assert_eq!(tree16.heap_size() * 4, tree64.heap_size());
+    }
+
+    #[test]
+    #[should_panic(expected = "bit vector is too long for the excess type")]
+    fn test_excess_type_too_narrow() {
+        let mut bv = BitVec::with_capacity(i16::MAX as usize + 1);
+        for _ in 0..=i16::MAX {
+            bv.append(true);
+        }
+        let _ = MinMaxTree::<i16>::excess_tree(&bv, 512);
+    }
+
+    #[test]
+    fn test_size_breakdown() {
+        let node_size = size_of::<ExcessNode<i64>>();
+        for len in [0, 1, 8, 9, 64, 100, 1000] {
+            let bv = BitVec::from_zeros(len);
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+            let breakdown = tree.size_breakdown();
+
+            assert_eq!(breakdown.node_count, tree.num_nodes());
+            assert_eq!(breakdown.leaf_nodes_bytes, len.div_ceil(8) * node_size);
+            assert_eq!(
+                breakdown.internal_nodes_bytes + breakdown.leaf_nodes_bytes,
+                tree.heap_size()
+            );
+        }
+
+        let breakdown =
+            MinMaxTree::<i16>::excess_tree(&BitVec::from_zeros(100), 8).size_breakdown();
+        assert_eq!(
+            breakdown.leaf_nodes_bytes,
+            13 * size_of::<ExcessNode<i16>>()
+        );
+        assert_eq!(
+            breakdown.internal_nodes_bytes,
+            15 * size_of::<ExcessNode<i16>>()
+        );
+    }
+
+    #[test]
+    fn test_leaves() {
+        let mut rng = StdRng::from_seed([33; 32]);
+        for len in [0, 1, 7, 8, 9, 64, 100, 333] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+            let num_leaves = len.div_ceil(8);
+
+            let leaves = tree.leaves().collect::<Vec<_>>();
+            assert_eq!(leaves.len(), num_leaves);
+            assert_eq!(tree.leaves().len(), num_leaves);
+
+            for (block, &(index, total, min, max)) in leaves.iter().enumerate() {
+                assert_eq!(index, block);
+
+                let mut excess = 0;
+                let mut expected_min = i64::MAX;
+                let mut expected_max = i64::MIN;
+                for i in block * 8..len.min(block * 8 + 8) {
+                    excess += if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+                    expected_min = expected_min.min(excess);
+                    expected_max = expected_max.max(excess);
+                }
+                assert_eq!((total, min, max), (excess, expected_min, expected_max));
+            }
+
+            let mut reversed = tree.leaves().rev().collect::<Vec<_>>();
+            reversed.reverse();
+            assert_eq!(reversed, leaves);
+        }
+    }
+
+    #[test]
+    fn test_range_min_excess() {
+        let mut rng = StdRng::from_seed([34; 32]);
+        for len in [1, 8, 9, 60, 129, 300] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+            let num_leaves = len.div_ceil(8);
+
+            // excess[k] is the excess of the first k bits
+            let mut excess = vec![0i64; len + 1];
+            for i in 0..len {
+                excess[i + 1] = excess[i] + if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+            }
+
+            for lo in 0..num_leaves {
+                for hi in lo + 1..=num_leaves {
+                    let expected = (lo * 8 + 1..=len.min(hi * 8))
+                        .map(|k| excess[k] - excess[lo * 8])
+                        .min()
+                        .unwrap();
+                    assert_eq!(
+                        tree.range_min_excess(lo, hi),
+                        expected,
+                        "blocks {lo}..{hi} of {num_leaves}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let mut rng = StdRng::from_seed([35; 32]);
+        for len in [0, 1, 8, 9, 100, 1000] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+            let mut bytes = Vec::new();
+            tree.serialize_to(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), 8 + tree.num_nodes() * 24);
+
+            let restored = MinMaxTree64::deserialize_from(&mut bytes.as_slice()).unwrap();
+            assert_eq!(restored.nodes, tree.nodes);
+
+            // the layout doesn't depend on the excess type
+            let narrow = MinMaxTree::<i16>::deserialize_from(&mut bytes.as_slice()).unwrap();
+            assert_eq!(narrow.nodes, MinMaxTree::<i16>::excess_tree(&bv, 8).nodes);
+            let mut narrow_bytes = Vec::new();
+            narrow.serialize_to(&mut narrow_bytes).unwrap();
+            assert_eq!(narrow_bytes, bytes);
+        }
+    }
+
+    #[test]
+    fn test_deserialization_errors() {
+        let bv = BitVec::from_bits(&[1, 1, 1, 0, 0, 1, 0, 0, 1, 0]);
+        let mut bytes = Vec::new();
+        MinMaxTree64::excess_tree(&bv, 4)
+            .serialize_to(&mut bytes)
+            .unwrap();
+
+        // truncated input
+        let error = MinMaxTree64::deserialize_from(&mut &bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // a node count that no tree has
+        let mut corrupted = bytes.clone();
+        corrupted[..8].copy_from_slice(&4u64.to_le_bytes());
+        let error = MinMaxTree64::deserialize_from(&mut corrupted.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // a value that doesn't fit into the excess type
+        let mut corrupted = bytes.clone();
+        corrupted[8..16].copy_from_slice(&(i64::from(i16::MAX) + 1).to_le_bytes());
+        assert!(MinMaxTree64::deserialize_from(&mut corrupted.as_slice()).is_ok());
+        let error = MinMaxTree::<i16>::deserialize_from(&mut corrupted.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_num_leaves_of() {
+        for num_leaves in 1..100 {
+            let tree = MinMaxTree64::rebuild_from_blocks(&vec![(0, 0, 0); num_leaves]);
+            assert_eq!(
+                MinMaxTree64::num_leaves_of(tree.num_nodes()),
+                Some(num_leaves)
+            );
+        }
+
+        for num_nodes in [0, 1, 4, 5, 8, 11, 16, 23] {
+            assert_eq!(MinMaxTree64::num_leaves_of(num_nodes), None, "{num_nodes}");
+        }
+    }
+
+    #[test]
+    fn test_try_excess_tree() {
+        let mut rng = StdRng::from_seed([37; 32]);
+        for len in [0, 1, 2, 7, 8, 9, 50, 300] {
+            for _ in 0..20 {
+                // bias towards opening parentheses to produce some valid prefixes
+                let mut bv = BitVec::with_capacity(len);
+                for _ in 0..len {
+                    bv.append(rng.gen_bool(0.6));
+                }
+
+                let mut excess = 0i64;
+                let mut expected = None;
+                let mut first_unclosed = 0;
+                for i in 0..len {
+                    excess += if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+                    if excess < 0 {
+                        expected = Some(ExcessError::UnmatchedClose { position: i });
+                        break;
+                    }
+                    if excess == 0 {
+                        first_unclosed = i + 1;
+                    }
+                }
+                if expected.is_none() && excess != 0 {
+                    expected = Some(ExcessError::UnclosedOpen {
+                        position: first_unclosed,
+                        excess,
+                    });
+                }
+
+                match MinMaxTree64::try_excess_tree(&bv, 8) {
+                    Ok(tree) => {
+                        assert_eq!(expected, None);
+                        assert_eq!(tree.nodes, MinMaxTree64::excess_tree(&bv, 8).nodes);
+                    }
+                    Err(error) => assert_eq!(Some(error), expected),
+                }
+            }
+        }
+
+        // a balanced expression
+        let bv = BitVec::from_bits(&[1, 1, 0, 1, 0, 0, 1, 0]);
+        assert!(MinMaxTree64::try_excess_tree(&bv, 4).is_ok());
+
+        let error = MinMaxTree64::try_excess_tree(&BitVec::from_bits(&[1, 1, 0, 0, 1, 1, 0]), 4)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ExcessError::UnclosedOpen {
+                position: 4,
+                excess: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "opening parenthesis at position 4 is never closed (1 unclosed parentheses in total)"
+        );
+    }
+
+    #[test]
+    fn test_fwd_search_inclusive() {
+        let mut rng = StdRng::from_seed([38; 32]);
+        for len in [0, 1, 8, 13, 64, 133] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 8);
+
+            // prefix[k] is the excess of the first k bits
+            let mut prefix = vec![0i64; len + 1];
+            for i in 0..len {
+                prefix[i + 1] = prefix[i] + if bv.is_bit_set_unchecked(i) { 1 } else { -1 };
+            }
+
+            for block in 0..len.div_ceil(8) {
+                for relative_excess in -6..=6 {
+                    let target = prefix[block * 8] + relative_excess;
+                    let expected = (block * 8..len)
+                        .find(|&i| prefix[i + 1] == target)
+                        .map(|i| (i / 8, target - prefix[i / 8 * 8]));
+                    assert_eq!(
+                        tree.fwd_search_inclusive(block, relative_excess),
+                        expected,
+                        "block {block} with {relative_excess} and length {len}"
+                    );
+                }
+            }
+
+            assert_eq!(tree.fwd_search_inclusive(len.div_ceil(8), 0), None);
+        }
+    }
+
+    #[test]
+    fn test_build_many() {
+        let mut rng = StdRng::from_seed([39; 32]);
+        let block_sizes = [1, 3, 8, 64, 1000];
+
+        for len in [0, 1, 7, 64, 65, 513, 4000] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+
+            let trees = MinMaxTree64::build_many(&bv, &block_sizes);
+            assert_eq!(trees.len(), block_sizes.len());
+            for (tree, &block_size) in trees.iter().zip(&block_sizes) {
+                assert_eq!(
+                    tree.nodes,
+                    MinMaxTree64::excess_tree(&bv, block_size).nodes,
+                    "length {len}, block size {block_size}"
+                );
+            }
+        }
+
+        assert!(MinMaxTree64::build_many(&BitVec::from_zeros(10), &[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "block size must be positive")]
+    fn test_build_many_zero_block_size() {
+        MinMaxTree64::build_many(&BitVec::from_zeros(10), &[8, 0]);
+    }
+
+    #[test]
+    fn test_num_blocks_and_depth() {
+        let tree = MinMaxTree64::excess_tree(&BitVec::new(), 8);
+        assert_eq!(tree.num_blocks(), 0);
+        assert_eq!(tree.depth(), 0);
+
+        // (number of bits, expected depth) for a block size of 8
+        for (len, depth) in [(1, 2), (8, 2), (9, 2), (17, 3), (32, 3), (33, 4), (512, 7)] {
+            let tree = MinMaxTree64::excess_tree(&BitVec::from_zeros(len), 8);
+            assert_eq!(tree.num_blocks(), len.div_ceil(8), "length {len}");
+            assert_eq!(tree.depth(), depth, "length {len}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_excess_tree_parallel() {
+        let mut rng = StdRng::from_seed([40; 32]);
+
+        for len in [0, 1, 7, 64, 65, 513, 10_000] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+
+            for block_size in [1, 3, 8, 64, 1000] {
+                assert_eq!(
+                    MinMaxTree64::excess_tree_parallel(&bv, block_size).nodes,
+                    MinMaxTree64::excess_tree(&bv, block_size).nodes,
+                    "length {len}, block size {block_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_enclosed_pairs() {
+        let mut rng = StdRng::from_seed([41; 32]);
+
+        for (len, block_size) in [(1, 1), (7, 3), (64, 8), (100, 8), (300, 16), (1000, 64)] {
+            // random bits produce plenty of pairs that cross block boundaries in both directions
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, block_size);
+            let num_blocks = len.div_ceil(block_size);
+
+            for block_lo in 0..=num_blocks {
+                for block_hi in block_lo..=num_blocks {
+                    let mut open = 0;
+                    let mut pairs = 0;
+                    for i in block_lo * block_size..(block_hi * block_size).min(len) {
+                        if bv.is_bit_set_unchecked(i) {
+                            open += 1;
+                        } else if open > 0 {
+                            open -= 1;
+                            pairs += 1;
+                        }
+                    }
+
+                    assert_eq!(
+                        tree.enclosed_pairs(len, block_size, block_lo, block_hi),
+                        pairs,
+                        "blocks {block_lo}..{block_hi} of length {len}, block size {block_size}"
+                    );
+                }
+            }
+        }
+
+        // a pair that spans two blocks only counts if both blocks are in the range
+        let bv = BitVec::from_balanced_string("(()( )())");
+        let tree = MinMaxTree64::excess_tree(&bv, 4);
+        assert_eq!(tree.enclosed_pairs(bv.len(), 4, 0, 1), 1);
+        assert_eq!(tree.enclosed_pairs(bv.len(), 4, 1, 2), 1);
+        assert_eq!(tree.enclosed_pairs(bv.len(), 4, 0, 2), 4);
+    }
+
+    #[test]
+    fn test_build_prefix_excess() {
+        let mut rng = StdRng::from_seed([42; 32]);
+
+        for len in [0, 1, 7, 64, 65, 513, 4000] {
+            let mut bv = BitVec::with_capacity(len);
+            for _ in 0..len {
+                bv.append(rng.gen_bool(0.5));
+            }
+            let tree = MinMaxTree64::excess_tree(&bv, 16);
+
+            let prefix = tree.build_prefix_excess();
+            assert_eq!(prefix.len(), tree.num_blocks() + 1);
+            assert_eq!(prefix[0], 0);
+            for (block, &excess) in prefix.iter().enumerate() {
+                assert_eq!(
+                    excess,
+                    tree.excess_prefix(block),
+                    "length {len}, block {block}"
+                );
+            }
+            assert_eq!(
+                tree.prefix_excess_heap_size(),
+                prefix.len() * size_of::<i64>()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "block size must be positive")]
+    fn test_zero_block_size() {
+        let _ = MinMaxTree64::excess_tree(&BitVec::from_bits(&[1, 0]), 0);
+    }
+
     #[test]
     fn test_relative_excess() {
         // test a tree with 3 layers and different downwards traversals
@@ -806,7 +2669,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 0,
         ]);
 
-        let tree = MinMaxTree::excess_tree(&bv, 8);
+        let tree = MinMaxTree64::excess_tree(&bv, 8);
 
         let block = tree.fwd_search(0, -6);
         assert!(block.is_some());
@@ -1552,6 +1552,117 @@ impl WaveletMatrix {
         }
     }
 
+    /// Find the most frequent symbol in the given `range`.
+    /// Returns the number of elements in the range that are smaller than the most frequent symbol,
+    /// such that the symbol can be retrieved with a quantile query, and the number of occurrences
+    /// of the symbol.
+    /// If multiple symbols are equally frequent, the smallest one is chosen.
+    ///
+    /// The function does not perform any checks, so the caller must ensure that the range is valid
+    /// and not empty.
+    fn range_mode_rank_unchecked(&self, range: Range<usize>) -> (usize, usize) {
+        debug_assert!(!range.is_empty());
+        debug_assert!(range.end <= self.len());
+
+        let mut best = (0, 0);
+        let mut smaller = 0;
+
+        // depth-first traversal of the partitions in ascending order of symbols, like in
+        // symbol_counts, but skipping partitions that are too small to contain a more frequent
+        // symbol
+        let mut stack = vec![(0, range)];
+        while let Some((level, range)) = stack.pop() {
+            if range.len() <= best.1 {
+                smaller += range.len();
+                continue;
+            }
+
+            if level == self.bits_per_element() {
+                best = (smaller, range.len());
+                smaller += range.len();
+                continue;
+            }
+
+            let data = &self.data[level];
+            let zeros_start = data.rank0(range.start);
+            let zeros_end = data.rank0(range.end);
+
+            stack.push((
+                level + 1,
+                data.rank0 + (range.start - zeros_start)..data.rank0 + (range.end - zeros_end),
+            ));
+            stack.push((level + 1, zeros_start..zeros_end));
+        }
+
+        best
+    }
+
+    /// Get the most frequent element in the encoded sequence in the specified `range`,
+    /// together with its number of occurrences in the range.
+    /// The range is a half-open interval, meaning that the `end` index is exclusive.
+    /// The element is returned as a `BitVec`, where the least significant bit is the first element.
+    /// If multiple elements are equally frequent, the smallest one is returned.
+    ///
+    /// The search skips all partitions of the matrix that are smaller than the most frequent
+    /// element found so far, but may still take `O(k * d)` rank queries in the worst case,
+    /// where `d` is the number of distinct elements in the range.
+    ///
+    /// Returns `None` if the `range` is out of bounds or if the range is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::{BitVec, WaveletMatrix};
+    ///
+    /// let bit_vec = BitVec::pack_sequence_u8(&[1, 4, 4, 1, 2, 7], 3);
+    /// let wavelet_matrix = WaveletMatrix::from_bit_vec(&bit_vec, 3);
+    ///
+    /// assert_eq!(wavelet_matrix.range_mode(0..3), Some((BitVec::pack_sequence_u8(&[4], 3), 2)));
+    /// assert_eq!(wavelet_matrix.range_mode(0..4), Some((BitVec::pack_sequence_u8(&[1], 3), 2)));
+    /// ```
+    #[must_use]
+    pub fn range_mode(&self, range: Range<usize>) -> Option<(BitVec, usize)> {
+        if range.is_empty() || range.end > self.len() {
+            None
+        } else {
+            let (k, count) = self.range_mode_rank_unchecked(range.clone());
+            Some((self.quantile_unchecked(range, k), count))
+        }
+    }
+
+    /// Get the most frequent element in the encoded sequence in the specified `range`,
+    /// together with its number of occurrences in the range.
+    /// The range is a half-open interval, meaning that the `end` index is exclusive.
+    /// The element is returned as a `u64` numeral.
+    /// If multiple elements are equally frequent, the smallest one is returned.
+    ///
+    /// See [`range_mode`] for the runtime.
+    ///
+    /// Returns `None` if the `range` is out of bounds, if the range is empty, or if the number of bits
+    /// per element exceeds 64.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::{BitVec, WaveletMatrix};
+    ///
+    /// let bit_vec = BitVec::pack_sequence_u8(&[1, 4, 4, 1, 2, 7], 3);
+    /// let wavelet_matrix = WaveletMatrix::from_bit_vec(&bit_vec, 3);
+    ///
+    /// assert_eq!(wavelet_matrix.range_mode_u64(0..3), Some((4, 2)));
+    /// assert_eq!(wavelet_matrix.range_mode_u64(0..4), Some((1, 2)));
+    /// assert_eq!(wavelet_matrix.range_mode_u64(3..3), None);
+    /// ```
+    ///
+    /// [`range_mode`]: WaveletMatrix::range_mode
+    #[must_use]
+    pub fn range_mode_u64(&self, range: Range<usize>) -> Option<(u64, usize)> {
+        if range.is_empty() || self.bits_per_element() > 64 || range.end > self.len() {
+            None
+        } else {
+            let (k, count) = self.range_mode_rank_unchecked(range.clone());
+            Some((self.quantile_u64_unchecked(range, k), count))
+        }
+    }
+
     /// Get the predecessor of the given `symbol` in the given `range`.
     /// This is a private generic helper function to implement the public `predecessor` functions.
     ///
@@ -1927,6 +2038,153 @@ impl WaveletMatrix {
         )
     }
 
+    /// Get the largest element smaller than or equal to `value` among the first `pos` elements of
+    /// the sequence, i.e. the [predecessor][WaveletMatrix::predecessor_u64] of `value` in the
+    /// range `0..pos`.
+    /// This supports cursor queries, where only the part of the sequence before the cursor
+    /// position is considered.
+    ///
+    /// Returns `None` under the same conditions as [`predecessor_u64`], in particular if `pos` is
+    /// zero or larger than the length of the sequence.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::WaveletMatrix;
+    ///
+    /// let wavelet_matrix = WaveletMatrix::from_slice(&[1, 4, 4, 1, 2, 7], 3);
+    ///
+    /// assert_eq!(wavelet_matrix.value_predecessor(3, 3), Some(1));
+    /// assert_eq!(wavelet_matrix.value_predecessor(5, 3), Some(2));
+    /// assert_eq!(wavelet_matrix.value_predecessor(3, 0), None);
+    /// ```
+    ///
+    /// [`predecessor_u64`]: WaveletMatrix::predecessor_u64
+    #[must_use]
+    pub fn value_predecessor(&self, pos: usize, value: u64) -> Option<u64> {
+        self.predecessor_u64(0..pos, value)
+    }
+
+    /// Get the smallest element greater than or equal to `value` among the first `pos` elements
+    /// of the sequence, i.e. the [successor][WaveletMatrix::successor_u64] of `value` in the range
+    /// `0..pos`.
+    ///
+    /// Returns `None` under the same conditions as [`successor_u64`], in particular if `pos` is
+    /// zero or larger than the length of the sequence.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::WaveletMatrix;
+    ///
+    /// let wavelet_matrix = WaveletMatrix::from_slice(&[1, 4, 4, 1, 2, 7], 3);
+    ///
+    /// assert_eq!(wavelet_matrix.value_successor(3, 2), Some(4));
+    /// assert_eq!(wavelet_matrix.value_successor(3, 5), None);
+    /// assert_eq!(wavelet_matrix.value_successor(6, 5), Some(7));
+    /// ```
+    ///
+    /// [`successor_u64`]: WaveletMatrix::successor_u64
+    #[must_use]
+    pub fn value_successor(&self, pos: usize, value: u64) -> Option<u64> {
+        self.successor_u64(0..pos, value)
+    }
+
+    /// Internal function that counts the occurrences of every distinct symbol in the `range`.
+    /// The counts are returned in ascending order of the symbols, and symbols that do not occur
+    /// in the range are omitted.
+    /// This takes `O(k * d)` rank queries, where `d` is the number of distinct symbols in the range.
+    ///
+    /// The function does not perform any checks, so the caller must ensure that the range is valid.
+    fn symbol_counts(&self, range: Range<usize>) -> Vec<usize> {
+        let mut counts = Vec::new();
+
+        if range.is_empty() || self.bits_per_element() == 0 {
+            return counts;
+        }
+
+        // depth-first traversal of all non-empty partitions of the matrix, visiting the 0-partition
+        // first so the symbols are visited in ascending order
+        let mut stack = vec![(0, range)];
+        while let Some((level, range)) = stack.pop() {
+            if level == self.bits_per_element() {
+                counts.push(range.len());
+                continue;
+            }
+
+            let data = &self.data[level];
+            let zeros_start = data.rank0(range.start);
+            let zeros_end = data.rank0(range.end);
+
+            let ones =
+                data.rank0 + (range.start - zeros_start)..data.rank0 + (range.end - zeros_end);
+            if !ones.is_empty() {
+                stack.push((level + 1, ones));
+            }
+            if zeros_start < zeros_end {
+                stack.push((level + 1, zeros_start..zeros_end));
+            }
+        }
+
+        counts
+    }
+
+    /// Get the zeroth-order empirical entropy `H0` of the encoded sequence in bits per element.
+    /// The entropy is computed from the frequencies `f_s` of all symbols `s` in the sequence as
+    /// `H0 = -Σ (f_s / n) * log2(f_s / n)`, and gives a lower bound for the number of bits per
+    /// element needed by any encoding that compresses each element independently.
+    ///
+    /// The runtime is `O(k * d)` rank queries, where `d` is the number of distinct symbols in the
+    /// sequence.
+    /// An empty matrix has an entropy of `0`.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::{BitVec, WaveletMatrix};
+    ///
+    /// let bit_vec = BitVec::pack_sequence_u8(&[1, 4, 4, 1, 2, 7, 2, 7], 3);
+    /// let wavelet_matrix = WaveletMatrix::from_bit_vec(&bit_vec, 3);
+    ///
+    /// // four equally frequent symbols
+    /// assert_eq!(wavelet_matrix.zeroth_order_entropy(), 2.0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // precision loss is acceptable for an estimate
+    pub fn zeroth_order_entropy(&self) -> f64 {
+        let n = self.len() as f64;
+        self.symbol_counts(0..self.len())
+            .into_iter()
+            .map(|count| {
+                let p = count as f64 / n;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Get the number of distinct elements in the encoded sequence in the specified `range`.
+    /// The range is a half-open interval, meaning that the `end` index is exclusive.
+    /// Positions beyond the end of the sequence are ignored, so an out-of-bounds range
+    /// counts the elements up to the end of the sequence.
+    ///
+    /// The elements are counted by a traversal of all partitions of the matrix that contain at
+    /// least one element of the range, so the runtime is `O(k * d)` rank queries,
+    /// where `d` is the number of distinct elements in the range.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::{BitVec, WaveletMatrix};
+    ///
+    /// let bit_vec = BitVec::pack_sequence_u8(&[1, 4, 4, 1, 2, 7], 3);
+    /// let wavelet_matrix = WaveletMatrix::from_bit_vec(&bit_vec, 3);
+    ///
+    /// assert_eq!(wavelet_matrix.range_distinct(0..4), 2);
+    /// assert_eq!(wavelet_matrix.range_distinct(0..6), 4);
+    /// assert_eq!(wavelet_matrix.range_distinct(3..3), 0);
+    /// ```
+    #[must_use]
+    pub fn range_distinct(&self, range: Range<usize>) -> usize {
+        let end = range.end.min(self.len());
+        self.symbol_counts(range.start.min(end)..end).len()
+    }
+
     /// Get an iterator over the elements of the encoded sequence.
     /// The iterator yields `u64` elements.
     /// If the number of bits per element exceeds 64, `None` is returned.
@@ -1942,6 +2200,7 @@ impl WaveletMatrix {
     /// assert_eq!(iter.collect::<Vec<_>>(), vec![1, 4, 4, 1, 2, 7]);
     /// ```
     #[must_use]
+    #[allow(unknown_lints, mismatched_lifetime_syntaxes)] // matches the other iterator getters
     pub fn iter_u64(&self) -> Option<WaveletNumRefIter> {
         if self.bits_per_element() > 64 {
             None
@@ -1967,6 +2226,7 @@ impl WaveletMatrix {
     ///
     /// See also [`iter_sorted_u64`] for an iterator that yields `u64` elements.
     #[must_use]
+    #[allow(unknown_lints, mismatched_lifetime_syntaxes)] // matches the other iterator getters
     pub fn iter_sorted(&self) -> WaveletSortedRefIter {
         WaveletSortedRefIter::new(self)
     }
@@ -1993,6 +2253,7 @@ impl WaveletMatrix {
     /// assert_eq!(iter.collect::<Vec<_>>(), vec![1, 1, 2, 4, 4, 7]);
     /// ```
     #[must_use]
+    #[allow(unknown_lints, mismatched_lifetime_syntaxes)] // matches the other iterator getters
     pub fn iter_sorted_u64(&self) -> Option<WaveletSortedNumRefIter> {
         if self.bits_per_element() > 64 {
             None
@@ -2013,6 +2274,56 @@ impl WaveletMatrix {
         }
     }
 
+    /// Decode the entire encoded sequence into a [`BitVec`] that stores the elements packed at
+    /// [`bits_per_element`] bits each, in the layout expected by [`from_bit_vec`].
+    ///
+    /// Instead of accessing each element individually, the levels are traversed once each in
+    /// sequential order, tracking the permutation of the elements between the levels.
+    /// This takes `O(kn)` time and `O(n)` additional space.
+    ///
+    /// # Example
+    /// ```
+    /// use vers_vecs::{BitVec, WaveletMatrix};
+    ///
+    /// let bit_vec = BitVec::pack_sequence_u8(&[1, 4, 4, 1, 2, 7], 3);
+    /// let wavelet_matrix = WaveletMatrix::from_bit_vec(&bit_vec, 3);
+    ///
+    /// assert_eq!(wavelet_matrix.to_bit_vec(), bit_vec);
+    /// ```
+    ///
+    /// [`bits_per_element`]: WaveletMatrix::bits_per_element
+    /// [`from_bit_vec`]: WaveletMatrix::from_bit_vec
+    #[must_use]
+    pub fn to_bit_vec(&self) -> BitVec {
+        let bits_per_element = self.bits_per_element();
+        let mut result = BitVec::from_zeros(self.len() * bits_per_element);
+
+        // the original index of the element at each position of the current level
+        let mut permutation = (0..self.len()).collect::<Vec<_>>();
+        let mut next_permutation = Vec::with_capacity(self.len());
+
+        for (level, data) in self.data.iter().enumerate() {
+            let bit_offset = bits_per_element - level - 1;
+            let mut ones = Vec::with_capacity(data.rank1);
+
+            for (pos, &element) in permutation.iter().enumerate() {
+                let bit = data.get_unchecked(pos);
+                result.set_unchecked(element * bits_per_element + bit_offset, bit);
+                if bit == 0 {
+                    next_permutation.push(element);
+                } else {
+                    ones.push(element);
+                }
+            }
+
+            next_permutation.append(&mut ones);
+            std::mem::swap(&mut permutation, &mut next_permutation);
+            next_permutation.clear();
+        }
+
+        result
+    }
+
     /// Get the number of bits per element in the alphabet of the encoded sequence.
     #[must_use]
     #[inline(always)]
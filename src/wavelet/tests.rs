@@ -2,6 +2,7 @@ use super::*;
 use rand::prelude::StdRng;
 use rand::{Rng, SeedableRng};
 use std::cmp::{max, min};
+use std::collections::HashSet;
 
 #[test]
 fn test_wavelet_encoding_pc() {
@@ -764,3 +765,167 @@ fn test_from_padded_bitvec() {
     assert_eq!(matrix.rank(2, &BitVec::from_ones(1)), Some(1));
     assert_eq!(matrix.rank(3, &BitVec::from_zeros(1)), None);
 }
+
+#[test]
+fn test_zeroth_order_entropy() {
+    // "aaab"
+    let wavelet =
+        WaveletMatrix::from_slice(&[b'a' as u64, b'a' as u64, b'a' as u64, b'b' as u64], 8);
+    let expected = -(0.75 * 0.75f64.log2() + 0.25 * 0.25f64.log2());
+    assert!((wavelet.zeroth_order_entropy() - expected).abs() < 1e-12);
+
+    // a single symbol has no entropy
+    let wavelet = WaveletMatrix::from_slice(&[5, 5, 5, 5], 3);
+    assert_eq!(wavelet.zeroth_order_entropy(), 0.0);
+
+    // uniform distribution over 8 symbols
+    let wavelet = WaveletMatrix::from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 7, 6, 5, 4, 3, 2, 1, 0], 3);
+    assert!((wavelet.zeroth_order_entropy() - 3.0).abs() < 1e-12);
+
+    let wavelet = WaveletMatrix::from_bit_vec(&BitVec::new(), 4);
+    assert_eq!(wavelet.zeroth_order_entropy(), 0.0);
+}
+
+#[test]
+fn test_symbol_counts_randomized() {
+    let mut rng = StdRng::from_seed([3; 32]);
+    let sequence = (0..2000)
+        .map(|_| rng.gen_range(0..40))
+        .collect::<Vec<u64>>();
+    let wavelet = WaveletMatrix::from_slice(&sequence, 6);
+
+    for range in [0..2000, 100..300, 1500..1501, 7..7] {
+        let mut expected = vec![0; 64];
+        for &v in &sequence[range.clone()] {
+            expected[v as usize] += 1;
+        }
+        expected.retain(|&c| c > 0);
+        assert_eq!(wavelet.symbol_counts(range), expected);
+    }
+}
+
+#[test]
+fn test_range_mode() {
+    let mut rng = StdRng::from_seed([4; 32]);
+    let sequence = (0..1000)
+        .map(|_| rng.gen_range(0..20))
+        .collect::<Vec<u64>>();
+    let wavelet = WaveletMatrix::from_slice(&sequence, 5);
+
+    for _ in 0..200 {
+        let start = rng.gen_range(0..sequence.len());
+        let end = rng.gen_range(start + 1..=sequence.len());
+
+        let mut histogram = vec![0; 32];
+        for &v in &sequence[start..end] {
+            histogram[v as usize] += 1;
+        }
+        let count = *histogram.iter().max().unwrap();
+        let mode = histogram.iter().position(|&c| c == count).unwrap() as u64;
+
+        assert_eq!(
+            wavelet.range_mode_u64(start..end),
+            Some((mode, count)),
+            "range {start}..{end}"
+        );
+        assert_eq!(
+            wavelet.range_mode(start..end),
+            Some((BitVec::pack_sequence_u64(&[mode], 5), count)),
+            "range {start}..{end}"
+        );
+    }
+
+    assert_eq!(wavelet.range_mode_u64(10..10), None);
+    assert_eq!(wavelet.range_mode(10..10), None);
+    assert_eq!(wavelet.range_mode_u64(10..1001), None);
+}
+
+#[test]
+fn test_range_mode_ties() {
+    let wavelet = WaveletMatrix::from_slice(&[5, 3, 5, 3, 7, 7], 3);
+    assert_eq!(wavelet.range_mode_u64(0..6), Some((3, 2)));
+    assert_eq!(wavelet.range_mode_u64(0..4), Some((3, 2)));
+    assert_eq!(wavelet.range_mode_u64(2..6), Some((7, 2)));
+}
+
+#[test]
+fn test_value_predecessor_successor() {
+    let mut rng = StdRng::from_seed([14; 32]);
+    let sequence = (0..300)
+        .map(|_| rng.gen_range(0..100u64))
+        .collect::<Vec<_>>();
+    let wavelet = WaveletMatrix::from_slice(&sequence, 7);
+
+    for pos in 0..=sequence.len() {
+        let prefix = &sequence[..pos];
+        for value in (0..128).step_by(3) {
+            let expected_pred = prefix.iter().copied().filter(|&v| v <= value).max();
+            let expected_succ = prefix.iter().copied().filter(|&v| v >= value).min();
+            assert_eq!(
+                wavelet.value_predecessor(pos, value),
+                expected_pred,
+                "predecessor of {value} before {pos}"
+            );
+            assert_eq!(
+                wavelet.value_successor(pos, value),
+                expected_succ,
+                "successor of {value} before {pos}"
+            );
+        }
+    }
+
+    assert_eq!(wavelet.value_predecessor(sequence.len() + 1, 50), None);
+    assert_eq!(wavelet.value_successor(sequence.len() + 1, 50), None);
+}
+
+#[test]
+fn test_to_bit_vec() {
+    let mut rng = StdRng::from_seed([15; 32]);
+    let sequence = (0..1000)
+        .map(|_| rng.gen_range(0..1000u64))
+        .collect::<Vec<_>>();
+    let wavelet = WaveletMatrix::from_slice(&sequence, 10);
+
+    let bit_vec = wavelet.to_bit_vec();
+    assert_eq!(bit_vec.len(), sequence.len() * 10);
+    for (i, &value) in sequence.iter().enumerate() {
+        assert_eq!(bit_vec.get_bits(i * 10, 10), wavelet.get_u64(i));
+        assert_eq!(bit_vec.get_bits(i * 10, 10), Some(value));
+    }
+
+    let round_trip = WaveletMatrix::from_bit_vec(&bit_vec, 10);
+    assert_eq!(round_trip.iter_u64().unwrap().collect::<Vec<_>>(), sequence);
+
+    let empty = WaveletMatrix::from_slice(&[], 4);
+    assert!(empty.to_bit_vec().is_empty());
+}
+
+#[test]
+fn test_range_distinct() {
+    let mut rng = StdRng::from_seed([16; 32]);
+    let sequence = (0..500)
+        .map(|_| rng.gen_range(0..40u64))
+        .collect::<Vec<_>>();
+    let wavelet = WaveletMatrix::from_slice(&sequence, 6);
+
+    for start in (0..sequence.len()).step_by(7) {
+        for end in (start..=sequence.len()).step_by(5) {
+            let expected = sequence[start..end].iter().collect::<HashSet<_>>().len();
+            assert_eq!(
+                wavelet.range_distinct(start..end),
+                expected,
+                "range {start}..{end}"
+            );
+        }
+    }
+
+    assert_eq!(wavelet.range_distinct(10..10), 0);
+    for i in 0..sequence.len() {
+        assert_eq!(wavelet.range_distinct(i..i + 1), 1);
+    }
+    assert_eq!(
+        wavelet.range_distinct(0..sequence.len() + 10),
+        wavelet.range_distinct(0..sequence.len())
+    );
+    assert_eq!(wavelet.range_distinct(600..700), 0);
+}